@@ -0,0 +1,211 @@
+use super::{html_links, line_ending, link_definitions};
+
+// ======================================================================
+// ALERTKIND - PUBLIC
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlertKind {
+    Caution,
+    Important,
+    Note,
+    Tip,
+    Warning,
+}
+
+// ======================================================================
+// FUNCTIONS - PUBLIC
+
+// > [!NOTE]
+//
+// Returns `None` if this is not the first line of an alert.
+pub fn alert_kind(line: &str) -> Option<AlertKind> {
+    let marker = blockquote_content(line)?.trim();
+    let name = marker.strip_prefix("[!")?.strip_suffix(']')?;
+
+    const DATA: &[(&str, AlertKind)] = &[
+        ("CAUTION", AlertKind::Caution),
+        ("IMPORTANT", AlertKind::Important),
+        ("NOTE", AlertKind::Note),
+        ("TIP", AlertKind::Tip),
+        ("WARNING", AlertKind::Warning),
+    ];
+
+    DATA.iter()
+        .find(|(data_name, _)| name.eq_ignore_ascii_case(data_name))
+        .map(|(_, kind)| *kind)
+}
+
+// Returns content of blockquote line without `>` and one optional space,
+// or `None` if this is not a blockquote line.
+pub fn blockquote_content(line: &str) -> Option<&str> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let content = line[indent..].strip_prefix('>')?;
+    Some(content.strip_prefix(' ').unwrap_or(content))
+}
+
+// Returns body of alert from lines after its first line: contents of blockquote lines,
+// and lazy continuation lines without `>` which continue a paragraph, like in CommonMark.
+pub fn alert_body<'a>(lines: &[&'a str]) -> Vec<&'a str> {
+    let mut body = Vec::new();
+    let mut is_in_code_block = false;
+    let mut is_in_paragraph = false;
+
+    for line in lines {
+        if let Some(content) = blockquote_content(line) {
+            let is_fence =
+                content.trim_start().starts_with("```") || content.trim_start().starts_with("~~~");
+            if is_fence {
+                is_in_code_block = !is_in_code_block;
+            }
+            is_in_paragraph =
+                !is_in_code_block && !is_fence && is_paragraph_text(content, is_in_paragraph);
+            body.push(content);
+        } else if is_in_paragraph && is_paragraph_text(line, true) {
+            body.push(*line);
+        } else {
+            break;
+        }
+    }
+    body
+}
+
+// Renders alert of given kind with already converted body.
+//
+// - Warnings and cautions are rendered as rustdoc warning blocks.
+// - Other alerts are rendered as blockquotes with a bold lead-in.
+pub fn render_alert(kind: AlertKind, marker_line: &str, body: &str) -> String {
+    let newline = match line_ending(marker_line) {
+        "" => "\n",
+        newline => newline,
+    };
+    let ending = if body.is_empty() {
+        line_ending(marker_line)
+    } else {
+        line_ending(body)
+    };
+
+    let lead_in = match kind {
+        AlertKind::Caution | AlertKind::Warning => {
+            let body_newline = if ending.is_empty() { newline } else { "" };
+            return format!(
+                "<div class=\"warning\">{0}{0}{1}{2}{0}</div>{3}",
+                newline, body, body_newline, ending
+            );
+        }
+        AlertKind::Important => "Important",
+        AlertKind::Note => "Note",
+        AlertKind::Tip => "Tip",
+    };
+
+    let mut result = format!("> **{}:**{}", lead_in, line_ending(marker_line));
+    for line in body.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            result.push('>');
+        } else {
+            result.push_str("> ");
+        }
+        result.push_str(line);
+    }
+    result
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+// Returns `true` if line is text of a paragraph, continuing it if `is_in_paragraph`,
+// i.e. it isn't empty and doesn't start another block like a header or a list item.
+fn is_paragraph_text(line: &str, is_in_paragraph: bool) -> bool {
+    let text = line.trim_start_matches(' ');
+    if line.trim().is_empty() || (line.len() - text.len() > 3 && !is_in_paragraph) {
+        return false;
+    }
+    let header_level = text.len() - text.trim_start_matches('#').len();
+    let is_header = (1..=6).contains(&header_level)
+        && text[header_level..].starts_with([' ', '\t', '\r', '\n']);
+    let marks: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let is_thematic_break = marks.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|mark| marks.iter().all(|c| c == mark));
+
+    !is_header
+        && !is_thematic_break
+        && !text.starts_with("```")
+        && !text.starts_with("~~~")
+        && !text.starts_with('>')
+        && link_definitions::list_item_content_column(line).is_none()
+        && !html_links::is_html_block_start(line)
+}
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alert_kinds() {
+        assert_eq!(alert_kind("> [!NOTE]\n"), Some(AlertKind::Note));
+        assert_eq!(alert_kind(">[!tip]"), Some(AlertKind::Tip));
+        assert_eq!(
+            alert_kind("   > [!Warning]  \r\n"),
+            Some(AlertKind::Warning)
+        );
+        assert_eq!(alert_kind("> [!FOO]\n"), None);
+        assert_eq!(alert_kind("> [!NOTE] text\n"), None);
+        assert_eq!(alert_kind("    > [!NOTE]\n"), None);
+        assert_eq!(alert_kind("[!NOTE]\n"), None);
+    }
+
+    #[test]
+    fn blockquote_contents() {
+        assert_eq!(blockquote_content("> a\n"), Some("a\n"));
+        assert_eq!(blockquote_content(">  a\n"), Some(" a\n"));
+        assert_eq!(blockquote_content(">\n"), Some("\n"));
+        assert_eq!(blockquote_content("a\n"), None);
+    }
+
+    #[test]
+    fn alert_bodies() {
+        assert_eq!(
+            alert_body(&["> a\n", "> b\n", "c\n"]),
+            ["a\n", "b\n", "c\n"]
+        );
+        assert_eq!(
+            alert_body(&["> a\n", "b\n", "    c\n", "\n", "d\n"]),
+            ["a\n", "b\n", "    c\n"]
+        );
+        assert_eq!(alert_body(&["a\n"]), [""; 0]);
+        assert_eq!(alert_body(&[">\n", "a\n"]), ["\n"]);
+        assert_eq!(alert_body(&["> ```\n", "> a\n", "b\n"]), ["```\n", "a\n"]);
+        assert_eq!(
+            alert_body(&["> ```\n", "> ```\n", "b\n"]),
+            ["```\n", "```\n"]
+        );
+        for line in [
+            "# b\n", "- b\n", "1. b\n", "---\n", "```\n", "<div>\n", "\n",
+        ] {
+            assert_eq!(alert_body(&["> a\n", line]), ["a\n"], "{}", line);
+        }
+    }
+
+    #[test]
+    fn render_warning_without_final_newline() {
+        assert_eq!(
+            render_alert(AlertKind::Warning, "> [!WARNING]\n", "a"),
+            "<div class=\"warning\">\n\na\n\n</div>"
+        );
+    }
+
+    #[test]
+    fn render_note_with_empty_line() {
+        assert_eq!(
+            render_alert(AlertKind::Note, "> [!NOTE]\r\n", "a\r\n\r\nb\r\n"),
+            "> **Note:**\r\n> a\r\n>\r\n> b\r\n"
+        );
+    }
+}
@@ -0,0 +1,407 @@
+use super::{
+    alerts::blockquote_content, code_span_len, emoji, is_code_block_end, is_code_block_start,
+    WarningKind,
+};
+
+// ======================================================================
+// ANCHORS - PUBLIC
+
+// Headers of readme, for converting same-document fragment links
+// from GitHub header ids to rustdoc header ids.
+pub struct Anchors {
+    // (GitHub id, rustdoc id)
+    headers: Vec<(String, String)>,
+    // GitHub id of top-level header, which is removed
+    title: Option<String>,
+    // Ids given in HTML with `id="..."` or `name="..."`
+    html_ids: Vec<String>,
+}
+
+impl Anchors {
+    // Collects headers and HTML ids of given readme lines.
+    //
+    // With `emoji_shortcodes` rustdoc ids are derived from headers with
+    // shortcodes expanded, as they are in output, but GitHub ids are not.
+    pub fn collect(lines: &[&str], emoji_shortcodes: bool) -> Self {
+        let mut github_ids = IdMap::new(&[]);
+        let mut rustdoc_ids = IdMap::new(RUSTDOC_DEFAULT_IDS);
+
+        let mut anchors = Anchors {
+            headers: Vec::new(),
+            title: None,
+            html_ids: Vec::new(),
+        };
+
+        let mut is_first_header = true;
+        let mut code_block_level = None;
+
+        for line in lines {
+            // Headers and code blocks can also be within blockquotes.
+            let mut line = *line;
+            while let Some(content) = blockquote_content(line) {
+                line = content;
+            }
+
+            if let Some(level) = code_block_level {
+                if is_code_block_end(line, level) {
+                    code_block_level = None;
+                }
+                continue;
+            }
+
+            code_block_level = is_code_block_start(line);
+            if code_block_level.is_some() {
+                continue;
+            }
+
+            if let Some((level, text)) = header_text(line) {
+                let github_id = github_ids.derive(&github_slug(&text));
+                if level == 1 && is_first_header {
+                    anchors.title = Some(github_id);
+                } else {
+                    let rustdoc_text = if emoji_shortcodes {
+                        header_text(&emoji::expand_shortcodes(line)).map_or(text, |(_, text)| text)
+                    } else {
+                        text
+                    };
+                    let rustdoc_id = rustdoc_ids.derive(&rustdoc_slug(&rustdoc_text));
+                    anchors.headers.push((github_id, rustdoc_id));
+                }
+                is_first_header = false;
+            } else {
+                anchors.html_ids.extend(html_ids(line));
+            }
+        }
+
+        anchors
+    }
+
+    // Converts link target `#fragment` from GitHub header id to rustdoc header id.
+    //
+    // Unknown fragments are kept as-is, adding a warning.
+    pub fn convert_fragment(&self, target: &str, warnings: &mut Vec<WarningKind>) -> String {
+        let fragment = match target.strip_prefix('#') {
+            Some("") | None => return target.to_owned(),
+            Some(fragment) => fragment,
+        };
+
+        if let Some((_, rustdoc_id)) = self.headers.iter().find(|(id, _)| id == fragment) {
+            format!("#{}", rustdoc_id)
+        } else if self.title.as_deref() == Some(fragment) {
+            "crate".to_owned()
+        } else {
+            if !self.html_ids.iter().any(|id| id == fragment) {
+                warnings.push(WarningKind::UnknownHeaderLink(target.to_owned()));
+            }
+            target.to_owned()
+        }
+    }
+
+    // Converts inline links `[...](#fragment)` within given line,
+    // except within code spans.
+    pub fn convert_inline_links(&self, line: &str, warnings: &mut Vec<WarningKind>) -> String {
+        let mut result = String::with_capacity(line.len());
+        let mut pos = 0;
+
+        while let Some(offset) = line[pos..].find(['`', ']']) {
+            let start = pos + offset;
+            result.push_str(&line[pos..start]);
+
+            if line[start..].starts_with('`') {
+                let len = code_span_len(&line[start..]);
+                result.push_str(&line[start..start + len]);
+                pos = start + len;
+            } else if line[start..].starts_with("](#") {
+                let target_start = start + 2;
+                let target_end = line[target_start..]
+                    .find(|c: char| c == ')' || c.is_whitespace())
+                    .map_or(line.len(), |pos| target_start + pos);
+                result.push_str("](");
+                result.push_str(&self.convert_fragment(&line[target_start..target_end], warnings));
+                pos = target_end;
+            } else {
+                result.push(']');
+                pos = start + 1;
+            }
+        }
+
+        result.push_str(&line[pos..]);
+        result
+    }
+}
+
+// ======================================================================
+// IDMAP - PRIVATE
+
+// Generates unique ids by adding suffixes `-1`, `-2`, ... to duplicates.
+struct IdMap {
+    ids: Vec<(String, usize)>,
+}
+
+impl IdMap {
+    // Ids in `reserved` get suffix `-1` already on first use.
+    fn new(reserved: &[&str]) -> Self {
+        Self {
+            ids: reserved.iter().map(|id| (id.to_string(), 1)).collect(),
+        }
+    }
+
+    fn derive(&mut self, candidate: &str) -> String {
+        let id = match self.ids.iter_mut().find(|(id, _)| id == candidate) {
+            Some((_, count)) => {
+                let id = format!("{}-{}", candidate, count);
+                *count += 1;
+                id
+            }
+            None => candidate.to_owned(),
+        };
+        self.ids.push((id.clone(), 1));
+        id
+    }
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+// Returns level and plain text of header line, if this is a header line.
+fn header_text(line: &str) -> Option<(usize, String)> {
+    let level = line.len() - line.trim_start_matches('#').len();
+    if level == 0 || line.as_bytes().get(level) != Some(&b' ') {
+        return None;
+    }
+
+    let text = line[level..].trim().trim_end_matches('#').trim_end();
+
+    // Remove inline markup: `code`, *emphasis*, [links](url), ![images](url), <html>
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '`' | '*' | '[' => {}
+            '!' if chars.peek() == Some(&'[') => {}
+            ']' if chars.peek() == Some(&'(') => {
+                for c in chars.by_ref() {
+                    if c == ')' {
+                        break;
+                    }
+                }
+            }
+            ']' => {}
+            '<' => {
+                for c in chars.by_ref() {
+                    if c == '>' {
+                        break;
+                    }
+                }
+            }
+            c => result.push(c),
+        }
+    }
+
+    Some((level, result))
+}
+
+// Returns values of `id="..."` and `name="..."` attributes in given line.
+fn html_ids(line: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    for attribute in [" id=\"", " name=\""] {
+        let mut rest = line;
+        while let Some(pos) = rest.find(attribute) {
+            rest = &rest[pos + attribute.len()..];
+            if let Some(end) = rest.find('"') {
+                ids.push(rest[..end].to_owned());
+                rest = &rest[end..];
+            }
+        }
+    }
+    ids
+}
+
+// Header id as generated by GitHub.
+fn github_slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| {
+            if c == ' ' {
+                Some('-')
+            } else if c.is_alphanumeric() || c == '_' || c == '-' {
+                Some(c)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// Header id as generated by rustdoc.
+fn rustdoc_slug(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|c| {
+            if c.is_alphanumeric() || c == '_' || c == '-' {
+                Some(c.to_ascii_lowercase())
+            } else if c.is_ascii_whitespace() {
+                Some('-')
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// ======================================================================
+// DATA
+
+// Ids used by rustdoc itself on a crate page.
+const RUSTDOC_DEFAULT_IDS: &[&str] = &[
+    "aliased-type",
+    "all-types",
+    "alternative-display",
+    "blanket-implementations",
+    "blanket-implementations-list",
+    "constants",
+    "copy-path",
+    "crate-search",
+    "crate-search-div",
+    "default-settings",
+    "deref-methods",
+    "enums",
+    "fields",
+    "foreign-impls",
+    "functions",
+    "help",
+    "implementations",
+    "implementations-list",
+    "implementors",
+    "implementors-list",
+    "layout",
+    "macros",
+    "main-content",
+    "modules",
+    "not-displayed",
+    "provided-associated-consts",
+    "provided-associated-types",
+    "provided-methods",
+    "reexports",
+    "required-associated-consts",
+    "required-associated-types",
+    "required-methods",
+    "rustdoc-modnav",
+    "rustdoc-toc",
+    "search",
+    "settings",
+    "sidebar-vars",
+    "statics",
+    "structs",
+    "synthetic-implementations",
+    "synthetic-implementations-list",
+    "synthetic-implementors",
+    "synthetic-implementors-list",
+    "toggle-all-docs",
+    "trait-implementations",
+    "trait-implementations-list",
+    "traits",
+    "types",
+    "unions",
+    "variants",
+];
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn anchors(readme: &str) -> Anchors {
+        let lines: Vec<&str> = readme.split_inclusive('\n').collect();
+        Anchors::collect(&lines, false)
+    }
+
+    fn convert(anchors: &Anchors, target: &str) -> (String, Vec<WarningKind>) {
+        let mut warnings = Vec::new();
+        let result = anchors.convert_fragment(target, &mut warnings);
+        (result, warnings)
+    }
+
+    #[test]
+    fn slugs() {
+        assert_eq!(github_slug("Link conversions"), "link-conversions");
+        assert_eq!(rustdoc_slug("Link conversions"), "link-conversions");
+        assert_eq!(github_slug("What's new?"), "whats-new");
+        assert_eq!(rustdoc_slug("What's new?"), "whats-new");
+        assert_eq!(github_slug("Foo::new_a"), "foonew_a");
+        assert_eq!(rustdoc_slug("Foo::new_a"), "foonew_a");
+        assert_eq!(github_slug("Ä b"), "ä-b");
+        assert_eq!(rustdoc_slug("Ä b"), "Ä-b");
+    }
+
+    #[test]
+    fn header_texts() {
+        assert_eq!(header_text("## Foo ##\n"), Some((2, "Foo".to_owned())));
+        assert_eq!(
+            header_text("### `Foo::new` and [*bar*](https://x)\n"),
+            Some((3, "Foo::new and bar".to_owned()))
+        );
+        assert_eq!(header_text("##Foo\n"), None);
+        assert_eq!(header_text("Foo\n"), None);
+    }
+
+    #[test]
+    fn duplicates_and_reserved_ids() {
+        let anchors = anchors("# Title\n## A\n## A\n```\n## A\n```\n## Modules\n");
+        assert_eq!(convert(&anchors, "#a"), ("#a".to_owned(), vec![]));
+        assert_eq!(convert(&anchors, "#a-1"), ("#a-1".to_owned(), vec![]));
+        assert_eq!(
+            convert(&anchors, "#modules"),
+            ("#modules-1".to_owned(), vec![])
+        );
+        assert_eq!(
+            convert(&anchors, "#a-2"),
+            (
+                "#a-2".to_owned(),
+                vec![WarningKind::UnknownHeaderLink("#a-2".to_owned())]
+            )
+        );
+    }
+
+    #[test]
+    fn title_and_html_ids() {
+        let anchors = anchors("# Title\n<a name=\"x\"></a>\n> ## Quoted\n");
+        assert_eq!(convert(&anchors, "#title"), ("crate".to_owned(), vec![]));
+        assert_eq!(convert(&anchors, "#x"), ("#x".to_owned(), vec![]));
+        assert_eq!(convert(&anchors, "#quoted"), ("#quoted".to_owned(), vec![]));
+        assert_eq!(convert(&anchors, "#"), ("#".to_owned(), vec![]));
+    }
+
+    #[test]
+    fn emoji_shortcodes() {
+        let lines = ["## :rocket: Fast\n"];
+        assert_eq!(
+            convert(&Anchors::collect(&lines, true), "#rocket-fast"),
+            ("#-fast".to_owned(), vec![])
+        );
+        assert_eq!(
+            convert(&Anchors::collect(&lines, false), "#rocket-fast"),
+            ("#rocket-fast".to_owned(), vec![])
+        );
+    }
+
+    #[test]
+    fn inline_links() {
+        let anchors = anchors("## Modules\n");
+        let mut warnings = Vec::new();
+        assert_eq!(
+            anchors.convert_inline_links(
+                "[a](#modules) `[b](#c)` [c] [d](#modules \"t\") [e](#L10)\n",
+                &mut warnings
+            ),
+            "[a](#modules-1) `[b](#c)` [c] [d](#modules-1 \"t\") [e](#L10)\n"
+        );
+        assert_eq!(
+            warnings,
+            [WarningKind::UnknownHeaderLink("#L10".to_owned())]
+        );
+    }
+}
@@ -0,0 +1,486 @@
+use super::{line_ending, WarningKind};
+
+// ======================================================================
+// FUNCTIONS - PUBLIC
+
+// <!-- rustdocify:attributes compile_fail, edition2021 -->
+//
+// Returns attributes if this is an attributes marker line.
+pub fn attributes_marker(line: &str) -> Option<Vec<String>> {
+    let attributes = line
+        .trim()
+        .strip_prefix("<!-- rustdocify:attributes")?
+        .strip_suffix("-->")?;
+    if !attributes.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(info_words(attributes).map(str::to_owned).collect())
+}
+
+// <!-- rustdocify:prelude
+// use foo::Bar;
+// -->
+//
+// Returns prelude lines, without line endings, and number of lines used
+// if given lines start with a prelude marker.
+pub fn prelude_marker(lines: &[&str]) -> Option<(Vec<String>, usize)> {
+    let first = lines
+        .first()?
+        .trim_start()
+        .strip_prefix("<!-- rustdocify:prelude")?;
+    if !first.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let mut prelude = Vec::new();
+    for (n, line) in std::iter::once(first)
+        .chain(lines[1..].iter().copied())
+        .enumerate()
+    {
+        if let Some(pos) = line.find("-->") {
+            prelude.extend(Some(line[..pos].trim()).filter(|line| !line.is_empty()));
+            return Some((prelude.into_iter().map(str::to_owned).collect(), n + 1));
+        }
+        if n > 0 || !line.trim().is_empty() {
+            prelude.push(line.trim_end());
+        }
+    }
+
+    // no end of comment
+    None
+}
+
+// Converts code line ending with `// rustdoc:hidden` to hidden line.
+//
+// Returns `None` if line doesn't end with `// rustdoc:hidden`.
+pub fn hidden_line(line: &str) -> Option<String> {
+    let code = line
+        .trim_end()
+        .strip_suffix("// rustdoc:hidden")?
+        .trim_end();
+    let indent_len = code.len() - code.trim_start().len();
+    Some(format!(
+        "{}# {}{}",
+        &code[..indent_len],
+        &code[indent_len..],
+        line_ending(line)
+    ))
+}
+
+// Returns `true` if rustdoc considers code block with given info string
+// to be Rust code.
+pub fn is_rust(info: &str) -> bool {
+    let mut seen_rust = false;
+    let mut seen_other = false;
+    for word in info_words(info) {
+        if word == "rust" || is_rustdoc_attribute(word) {
+            seen_rust = true;
+        } else {
+            seen_other = true;
+        }
+    }
+    seen_rust || !seen_other
+}
+
+// Checks code block with given info string and content.
+pub fn lint(info: &str, content: &[&str]) -> Vec<WarningKind> {
+    let mut warnings = Vec::new();
+
+    if info.is_empty() {
+        if !content.iter().all(|line| line.trim().is_empty())
+            && !content.iter().any(|line| looks_like_rust(line))
+        {
+            warnings.push(WarningKind::UntaggedNonRustCodeBlock);
+        }
+        return warnings;
+    }
+
+    let is_rust = is_rust(info);
+    let words: Vec<&str> = info_words(info).collect();
+
+    for word in &words {
+        if word == &"rust" || is_rustdoc_attribute(word) {
+            continue;
+        }
+        if is_rust || is_attribute_typo(word) {
+            warnings.push(WarningKind::UnknownCodeBlockAttribute(word.to_string()));
+        }
+    }
+
+    for (a, b) in CONFLICTING_ATTRIBUTES {
+        if words.contains(a) && words.contains(b) {
+            warnings.push(WarningKind::ConflictingCodeBlockAttributes(
+                a.to_string(),
+                b.to_string(),
+            ));
+        }
+    }
+
+    warnings
+}
+
+// Returns words of code block info string,
+// split at whitespace and commas but not within double quotes.
+pub fn info_words(info: &str) -> impl Iterator<Item = &str> {
+    let mut rest = info;
+    std::iter::from_fn(move || {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        if rest.is_empty() {
+            return None;
+        }
+        let mut is_quoted = false;
+        let len = rest
+            .find(|c: char| {
+                if c == '"' {
+                    is_quoted = !is_quoted;
+                }
+                !is_quoted && (c == ',' || c.is_whitespace())
+            })
+            .unwrap_or(rest.len());
+        let word = &rest[..len];
+        rest = &rest[len..];
+        Some(word)
+    })
+}
+
+// Rewrites info string of code block start line with `backtick_count` backticks.
+//
+// - Language, i.e. first word or `""` if there is none, is replaced
+//   according to `infos` which has `(language, replacement)` pairs.
+// - `attributes` are appended.
+// - Words like `title="..."` are removed.
+//
+// Words added by replacement or `attributes` are skipped if those are already on the block
+// or conflict with attributes on the block, e.g. `no_run` with `ignore`.
+// Attributes on the block take precedence over `attributes`,
+// which take precedence over words added by replacement.
+// Rustdoc attributes aren't added to non-Rust code blocks.
+//
+// Returns `None` if there is nothing to rewrite.
+pub fn rewrite_info(
+    line: &str,
+    backtick_count: usize,
+    infos: &[(String, String)],
+    attributes: &[String],
+) -> Option<String> {
+    let info = line[backtick_count..].trim();
+    let mut words = info_words(info);
+    let language = words.next().unwrap_or("");
+
+    let replacement = infos
+        .iter()
+        .find(|(from, _)| from == language)
+        .map(|(_, to)| to.as_str());
+
+    if replacement.is_none() && attributes.is_empty() {
+        return None;
+    }
+
+    let mut replacement_words = info_words(replacement.unwrap_or(language));
+    let mut new_words: Vec<&str> = replacement_words.next().into_iter().collect();
+    let block_words: Vec<&str> = words.filter(|word| !word.contains('=')).collect();
+    let is_rust_block = is_rust(&[new_words.as_slice(), &block_words].concat().join(","));
+
+    let mut added_attributes: Vec<&str> = Vec::new();
+    for attribute in attributes {
+        if (is_rust_block || !is_rustdoc_attribute(attribute))
+            && !conflicts(attribute, &block_words)
+            && !added_attributes.contains(&attribute.as_str())
+        {
+            added_attributes.push(attribute);
+        }
+    }
+    for word in replacement_words {
+        if !conflicts(word, &block_words) && !conflicts(word, &added_attributes) {
+            new_words.push(word);
+        }
+    }
+    new_words.extend(block_words);
+    new_words.extend(added_attributes);
+
+    Some(format!(
+        "{}{}{}",
+        &line[..backtick_count],
+        new_words.join(","),
+        line_ending(line)
+    ))
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+fn is_rustdoc_attribute(word: &str) -> bool {
+    RUSTDOC_ATTRIBUTES.contains(&word)
+        || word.starts_with("ignore-")
+        || word.starts_with("edition")
+        || (word.len() == 5
+            && word.starts_with('E')
+            && word[1..].bytes().all(|b| b.is_ascii_digit()))
+}
+
+// Returns `true` if word is in `words` or conflicts with any of them, e.g. `no_run` with `ignore`.
+fn conflicts(word: &str, words: &[&str]) -> bool {
+    words.contains(&word)
+        || CONFLICTING_ATTRIBUTES
+            .iter()
+            .any(|&(a, b)| (word == a && words.contains(&b)) || (word == b && words.contains(&a)))
+}
+
+// Returns `true` if word is close to a rustdoc attribute, e.g. `no-run` or `ignored`.
+fn is_attribute_typo(word: &str) -> bool {
+    word.len() >= 5
+        && RUSTDOC_ATTRIBUTES
+            .iter()
+            .chain(&["edition2015", "edition2018", "edition2021", "edition2024"])
+            .any(|attribute| edit_distance(&word.to_ascii_lowercase(), attribute) <= 2)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// Returns `true` if line of code looks like Rust.
+fn looks_like_rust(line: &str) -> bool {
+    let line = line.trim();
+    line.ends_with(';')
+        || line.ends_with('{')
+        || line.starts_with('}')
+        || line.starts_with("//")
+        || line.starts_with("#[")
+        || line.starts_with("#![")
+        || line.starts_with("# ")
+        || RUST_LINE_STARTS.iter().any(|start| line.starts_with(start))
+}
+
+// ======================================================================
+// DATA
+
+const CONFLICTING_ATTRIBUTES: &[(&str, &str)] = &[
+    ("compile_fail", "no_run"),
+    ("compile_fail", "should_panic"),
+    ("ignore", "compile_fail"),
+    ("ignore", "no_run"),
+    ("ignore", "should_panic"),
+    ("no_run", "should_panic"),
+];
+
+const RUST_LINE_STARTS: &[&str] = &[
+    "assert", "const ", "enum ", "extern ", "fn ", "impl ", "let ", "mod ", "pub ", "struct ",
+    "trait ", "type ", "use ",
+];
+
+// Rustdoc code block attributes, excluding `ignore-*`, `edition*` and error codes.
+const RUSTDOC_ATTRIBUTES: &[&str] = &[
+    "compile_fail",
+    "ignore",
+    "no_run",
+    "should_panic",
+    "standalone_crate",
+    "test_harness",
+];
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn infos() -> Vec<(String, String)> {
+        [("rust", "rust,no_run"), ("", "text"), ("console", "text")]
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn attributes_markers() {
+        assert_eq!(
+            attributes_marker("<!-- rustdocify:attributes compile_fail, edition2021 -->\n"),
+            Some(vec!["compile_fail".to_owned(), "edition2021".to_owned()])
+        );
+        assert_eq!(attributes_marker("<!-- rustdocify:attributesx -->"), None);
+        assert_eq!(attributes_marker("<!-- foo -->"), None);
+    }
+
+    #[test]
+    fn prelude_markers() {
+        let lines = [
+            "<!-- rustdocify:prelude\n",
+            "use a::B;\n",
+            "\n",
+            "fn f() {} -->\n",
+            "x",
+        ];
+        assert_eq!(
+            prelude_marker(&lines),
+            Some((
+                vec![
+                    "use a::B;".to_owned(),
+                    "".to_owned(),
+                    "fn f() {}".to_owned()
+                ],
+                4
+            ))
+        );
+        assert_eq!(
+            prelude_marker(&["<!-- rustdocify:prelude use a::B; -->\n"]),
+            Some((vec!["use a::B;".to_owned()], 1))
+        );
+        assert_eq!(
+            prelude_marker(&["<!-- rustdocify:prelude\n", "use a::B;\n"]),
+            None
+        );
+        assert_eq!(prelude_marker(&["<!-- rustdocify:preludes -->\n"]), None);
+    }
+
+    #[test]
+    fn hidden_lines() {
+        assert_eq!(
+            hidden_line("    use a::B; // rustdoc:hidden  \r\n"),
+            Some("    # use a::B;\r\n".to_owned())
+        );
+        assert_eq!(hidden_line("use a::B;\n"), None);
+    }
+
+    #[test]
+    fn rust_infos() {
+        assert!(is_rust(""));
+        assert!(is_rust("rust"));
+        assert!(is_rust("no_run"));
+        assert!(is_rust("ignore-wasm32,edition2021"));
+        assert!(is_rust("rust,foo"));
+        assert!(!is_rust("text"));
+        assert!(!is_rust("toml"));
+    }
+
+    #[test]
+    fn lint_untagged() {
+        assert_eq!(
+            lint("", &["$ cargo run\n"]),
+            [WarningKind::UntaggedNonRustCodeBlock]
+        );
+        assert_eq!(lint("", &["let a = 1;\n"]), []);
+        assert_eq!(lint("", &["\n"]), []);
+        assert_eq!(lint("text", &["$ cargo run\n"]), []);
+    }
+
+    #[test]
+    fn lint_unknown_attributes() {
+        assert_eq!(
+            lint("no-run", &[]),
+            [WarningKind::UnknownCodeBlockAttribute("no-run".to_owned())]
+        );
+        assert_eq!(
+            lint("rust,ignored", &[]),
+            [WarningKind::UnknownCodeBlockAttribute("ignored".to_owned())]
+        );
+        assert_eq!(
+            lint("no_run,foo", &[]),
+            [WarningKind::UnknownCodeBlockAttribute("foo".to_owned())]
+        );
+        assert_eq!(lint("toml", &[]), []);
+        assert_eq!(lint("console", &[]), []);
+        assert_eq!(lint("rust,edition2021,ignore-wasm32,E0123", &[]), []);
+    }
+
+    #[test]
+    fn lint_conflicting_attributes() {
+        assert_eq!(
+            lint("ignore,should_panic", &[]),
+            [WarningKind::ConflictingCodeBlockAttributes(
+                "ignore".to_owned(),
+                "should_panic".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("no-run", "no_run"), 1);
+        assert_eq!(edit_distance("ignored", "ignore"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn words() {
+        let words: Vec<_> = info_words(" rust,ignore  title=\"a b\"").collect();
+        assert_eq!(words, ["rust", "ignore", "title=\"a b\""]);
+    }
+
+    #[test]
+    fn rewrite() {
+        let infos = infos();
+        assert_eq!(
+            rewrite_info("```rust\n", 3, &infos, &[]),
+            Some("```rust,no_run\n".to_owned())
+        );
+        assert_eq!(
+            rewrite_info("````\r\n", 4, &infos, &[]),
+            Some("````text\r\n".to_owned())
+        );
+        assert_eq!(
+            rewrite_info("```console", 3, &infos, &[]),
+            Some("```text".to_owned())
+        );
+        assert_eq!(
+            rewrite_info("```rust title=\"main.rs\" should_panic\n", 3, &infos, &[]),
+            Some("```rust,should_panic\n".to_owned())
+        );
+        assert_eq!(rewrite_info("```toml\n", 3, &infos, &[]), None);
+    }
+
+    #[test]
+    fn rewrite_without_conflicting_attributes() {
+        let infos = infos();
+        assert_eq!(
+            rewrite_info("```rust,ignore\n", 3, &infos, &[]),
+            Some("```rust,ignore\n".to_owned())
+        );
+        assert_eq!(
+            rewrite_info("```rust,compile_fail\n", 3, &infos, &[]),
+            Some("```rust,compile_fail\n".to_owned())
+        );
+        assert_eq!(
+            rewrite_info("```rust,no_run\n", 3, &infos, &[]),
+            Some("```rust,no_run\n".to_owned())
+        );
+
+        let attributes = ["should_panic".to_owned()];
+        assert_eq!(
+            rewrite_info("```rust\n", 3, &infos, &attributes),
+            Some("```rust,should_panic\n".to_owned())
+        );
+        assert_eq!(
+            rewrite_info("```rust,ignore\n", 3, &[], &attributes),
+            Some("```rust,ignore\n".to_owned())
+        );
+        assert_eq!(
+            rewrite_info("```toml\n", 3, &[], &attributes),
+            Some("```toml\n".to_owned())
+        );
+    }
+
+    #[test]
+    fn rewrite_with_attributes() {
+        let attributes = ["compile_fail".to_owned(), "edition2021".to_owned()];
+        assert_eq!(
+            rewrite_info("```rust\n", 3, &[], &attributes),
+            Some("```rust,compile_fail,edition2021\n".to_owned())
+        );
+        assert_eq!(
+            rewrite_info("```\n", 3, &[], &attributes),
+            Some("```compile_fail,edition2021\n".to_owned())
+        );
+    }
+}
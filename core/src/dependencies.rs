@@ -0,0 +1,418 @@
+use super::{code_blocks, toml::Toml, Error};
+
+// ======================================================================
+// FUNCTIONS - PUBLIC
+
+// Returns crate names usable in doctests of package with given `Cargo.toml`,
+// i.e. names of dependencies and dev-dependencies, including target-specific ones.
+//
+// Renamed dependencies are returned with their new name,
+// and `-` is replaced with `_` in all names.
+pub fn declared_crates(manifest: &str) -> Result<Vec<String>, Error> {
+    let toml = Toml::parse(manifest).map_err(Error::InvalidManifest)?;
+
+    let mut crates = Vec::new();
+    for entry in &toml.entries {
+        let table = match entry.table.first().map(String::as_str) {
+            Some("target") => entry.table.get(2..).unwrap_or_default(),
+            _ => &entry.table[..],
+        };
+        let name = match table {
+            [kind] if is_dependency_table(kind) => &entry.key[0],
+            [kind, name, ..] if is_dependency_table(kind) => name,
+            _ => continue,
+        };
+        let name = name.replace('-', "_");
+        if !crates.contains(&name) {
+            crates.push(name);
+        }
+    }
+    Ok(crates)
+}
+
+// Returns crates used by given lines of Rust code block which aren't
+// in `declared`, with 0-based index of the line where each is first used.
+//
+// Used crates are found from `use` declarations, `extern crate` items
+// and roots of paths like `foo::bar()`. Names brought into scope within
+// the code block itself, e.g. by `use std::io;` or `mod foo`, are not crates.
+pub fn undeclared_crates(lines: &[&str], declared: &[String]) -> Vec<(usize, String)> {
+    let lines: Vec<String> = lines.iter().map(|line| code(line)).collect();
+    let words: Vec<(usize, &str)> = lines
+        .iter()
+        .enumerate()
+        .flat_map(|(index, line)| words(line).map(move |word| (index, word)))
+        .collect();
+
+    let local_names = local_names_of(&words);
+
+    let mut result: Vec<(usize, String)> = Vec::new();
+    for (index, name) in path_roots(&words) {
+        if !declared.iter().any(|declared| declared == name)
+            && !BUILTIN_NAMES.contains(&name)
+            && !local_names.contains(&name)
+            && !result.iter().any(|(_, used)| used == name)
+        {
+            result.push((index, name.to_owned()));
+        }
+    }
+    result
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+fn is_dependency_table(name: &str) -> bool {
+    name == "dependencies" || name == "dev-dependencies"
+}
+
+// Returns code of given line, without hidden line marker `#`,
+// trailing `// rustdoc:hidden`, comments and contents of string and char literals.
+fn code(line: &str) -> String {
+    let line = code_blocks::hidden_line(line).unwrap_or_else(|| line.to_owned());
+    let trimmed = line.trim_start();
+    let line = match trimmed.strip_prefix('#') {
+        Some(rest) if !rest.starts_with(['[', '!']) => rest,
+        _ => trimmed,
+    };
+
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => break,
+            // char literal like `'"'` or `'\''`, but not lifetime like `'a`
+            '\'' if chars.peek() == Some(&'\\') || chars.clone().nth(1) == Some('\'') => {
+                result.push_str("''");
+                if chars.next() == Some('\\') {
+                    chars.next();
+                }
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                result.push_str("\"\"");
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+// Returns names brought into scope by `use`, `mod` and `extern crate ... as`
+// in given words of code, which are paired with line indices.
+//
+// `use` declarations continue until `;` and can span several lines.
+fn local_names_of<'a>(words: &[(usize, &'a str)]) -> Vec<&'a str> {
+    let words: Vec<&str> = words.iter().map(|(_, word)| *word).collect();
+    let mut names = Vec::new();
+    let mut is_in_use = false;
+
+    for (index, word) in words.iter().enumerate() {
+        let previous = if index > 0 { words[index - 1] } else { "" };
+        let next = words.get(index + 1).copied().unwrap_or("");
+        match *word {
+            "use" => is_in_use = true,
+            ";" => is_in_use = false,
+            _ => {}
+        }
+
+        if previous == "mod" || previous == "as" {
+            // `mod foo` or `use foo as bar`
+            if is_identifier(word) {
+                names.push(*word);
+            }
+        } else if previous == "{" || previous == "," {
+            // `use std::{foo, bar::baz, io::{self, Read}}`
+            let is_group = next == "::" && words.get(index + 2) == Some(&"{");
+            if (next != "::" || is_group) && is_identifier(word) && is_in_use {
+                names.push(*word);
+            }
+        } else if previous == "::" && next != "::" && next != "as" {
+            // `use std::foo`
+            if is_identifier(word) && is_in_use {
+                names.push(*word);
+            }
+        }
+    }
+
+    names
+}
+
+// Returns roots of paths in given words of code, e.g. `foo` of `foo::bar` and `use foo;`,
+// and names of crates in `extern crate foo`, with their line indices.
+//
+// Paths of `use` declaration have a root only at the start of the declaration
+// or of its brace groups without prefix, e.g. `foo` and `bar` of `use {foo::A, bar::{b::B}};`,
+// but not `b`. Declarations can span several lines.
+fn path_roots<'a>(words: &[(usize, &'a str)]) -> Vec<(usize, &'a str)> {
+    let mut roots = Vec::new();
+    // for each brace group within current `use` declaration,
+    // `true` if paths of the group start from root, i.e. group has no prefix
+    let mut use_groups: Option<Vec<bool>> = None;
+
+    for (index, &(line, word)) in words.iter().enumerate() {
+        let previous = if index > 0 { words[index - 1].1 } else { "" };
+        let next = words.get(index + 1).map_or("", |(_, word)| *word);
+        // `true` if word starts a path from root within `use` declaration,
+        // also after leading `::`
+        let is_use_path_start = |groups: &[bool]| {
+            let start = if previous == "::" { index - 1 } else { index };
+            match words[..start].last().map(|(_, word)| *word) {
+                Some("use") => true,
+                Some("{" | ",") => groups.last() == Some(&true),
+                _ => false,
+            }
+        };
+        match (word, &mut use_groups) {
+            ("use", _) => use_groups = Some(Vec::new()),
+            (";", _) => use_groups = None,
+            ("{", Some(groups)) => {
+                let is_root_group = is_use_path_start(groups);
+                groups.push(is_root_group);
+            }
+            ("}", Some(groups)) => {
+                groups.pop();
+            }
+            _ => {}
+        }
+
+        if !is_identifier(word) || word == "use" {
+            continue;
+        }
+        let is_path_root = match &use_groups {
+            Some(groups) => is_use_path_start(groups),
+            None => next == "::" && previous != "::",
+        };
+        let is_extern_crate = previous == "crate" && index >= 2 && words[index - 2].1 == "extern";
+        if is_path_root || is_extern_crate {
+            roots.push((line, word));
+        }
+    }
+    roots
+}
+
+// Splits line of code into identifiers, `::` and other single characters.
+fn words(line: &str) -> impl Iterator<Item = &str> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let rest = &line[pos..];
+        let rest_trimmed = rest.trim_start();
+        pos += rest.len() - rest_trimmed.len();
+        let c = rest_trimmed.chars().next()?;
+
+        let len = if c.is_alphanumeric() || c == '_' {
+            rest_trimmed
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest_trimmed.len())
+        } else if rest_trimmed.starts_with("::") {
+            2
+        } else {
+            c.len_utf8()
+        };
+
+        let word = &line[pos..pos + len];
+        pos += len;
+        Some(word)
+    })
+}
+
+// Returns `true` for lowercase identifiers which can be crate names.
+fn is_identifier(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && word.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && word != "_"
+}
+
+// ======================================================================
+// DATA
+
+// Names which can be path roots without being dependencies.
+const BUILTIN_NAMES: &[&str] = &[
+    "alloc",
+    "bool",
+    "char",
+    "core",
+    "crate",
+    "f32",
+    "f64",
+    "i128",
+    "i16",
+    "i32",
+    "i64",
+    "i8",
+    "isize",
+    "proc_macro",
+    "self",
+    "std",
+    "str",
+    "super",
+    "test",
+    "u128",
+    "u16",
+    "u32",
+    "u64",
+    "u8",
+    "usize",
+];
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn undeclared(code: &str, declared: &[&str]) -> Vec<(usize, String)> {
+        let lines: Vec<&str> = code.split_inclusive('\n').collect();
+        let declared: Vec<String> = declared.iter().map(|name| name.to_string()).collect();
+        undeclared_crates(&lines, &declared)
+    }
+
+    #[test]
+    fn declared_crates_of_manifest() {
+        let manifest = concat!(
+            "[package]\nname = \"foo\"\n",
+            "[dependencies]\nserde-json = \"1\"\nrand = { version = \"0.8\" }\n",
+            "[dependencies.regex]\nversion = \"1\"\n",
+            "[dev-dependencies]\nfutures = { package = \"futures-util\", version = \"0.3\" }\n",
+            "[build-dependencies]\ncc = \"1\"\n",
+            "[target.'cfg(unix)'.dev-dependencies]\nlibc = \"0.2\"\n",
+        );
+        assert_eq!(
+            declared_crates(manifest).unwrap(),
+            ["serde_json", "rand", "regex", "futures", "libc"]
+        );
+    }
+
+    #[test]
+    fn use_declarations() {
+        assert_eq!(
+            undeclared(
+                "use tokio::runtime;\nuse ::rand::Rng;\nuse foo::{a, b};\nuse bar;\n",
+                &["foo"]
+            ),
+            [
+                (0, "tokio".to_owned()),
+                (1, "rand".to_owned()),
+                (3, "bar".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn use_groups_without_prefix() {
+        assert_eq!(
+            undeclared(
+                concat!(
+                    "use {foo::X, futures::y};
+",
+                    "use ::{rand::Rng, foo::{bar::Baz, self}};
+",
+                    "use {{tokio::a}, foo};
+",
+                ),
+                &["foo"]
+            ),
+            [
+                (0, "futures".to_owned()),
+                (1, "rand".to_owned()),
+                (2, "tokio".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn multi_line_use_declarations() {
+        assert_eq!(
+            undeclared(
+                concat!(
+                    "use std::{\n    fmt,\n    io::Read,\n};\n",
+                    "use foo::{\n    bar::Baz,\n};\n",
+                    "use\n    tokio::runtime;\n",
+                    "fmt::Debug;\n",
+                ),
+                &["foo"]
+            ),
+            [(8, "tokio".to_owned())]
+        );
+    }
+
+    #[test]
+    fn extern_crate() {
+        assert_eq!(
+            undeclared("extern crate rand as r;\nr::random();\n", &[]),
+            [(0, "rand".to_owned())]
+        );
+    }
+
+    #[test]
+    fn path_roots_in_code() {
+        assert_eq!(
+            undeclared(
+                "#[tokio::main]\nasync fn main() {\n    let a = serde_json::to_string(&1);\n}\n",
+                &["tokio"]
+            ),
+            [(2, "serde_json".to_owned())]
+        );
+    }
+
+    #[test]
+    fn hidden_lines() {
+        assert_eq!(
+            undeclared("# use rand::Rng;\nrand::random(); // rustdoc:hidden\n", &[]),
+            [(0, "rand".to_owned())]
+        );
+    }
+
+    #[test]
+    fn ignore_local_and_builtin_names() {
+        assert_eq!(
+            undeclared(
+                concat!(
+                    "use std::{fmt, io::{self, Read}};\n",
+                    "use std::collections as c;\n",
+                    "mod m { pub fn f() {} }\n",
+                    "m::f(); io::stdin(); fmt::Error; c::HashMap::<u8, u8>::new();\n",
+                    "u32::MAX; Vec::<u8>::new(); self::m::f(); crate::m::f();\n",
+                ),
+                &[]
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn ignore_comments_and_strings() {
+        assert_eq!(
+            undeclared("let a = \"tokio::main \\\" rand::x\"; // serde::x\n", &[]),
+            []
+        );
+    }
+
+    #[test]
+    fn ignore_char_literals() {
+        assert_eq!(
+            undeclared(
+                concat!(
+                    "let a = ['\"', '\\'', 'x']; let b = \"tokio::x\";\n",
+                    "fn f<'a>(x: &'a str) -> &'a str { rand::x(x) }\n",
+                ),
+                &[]
+            ),
+            [(1, "rand".to_owned())]
+        );
+    }
+}
@@ -0,0 +1,513 @@
+use super::{intra_doc_links, DocsLayout, ItemKind};
+
+// ======================================================================
+// DOCSURL - PUBLIC
+
+// URL of documentation of the package, split to path segments, query and fragment.
+//
+// `https://docs.rs/foo/0.1.0/foo/a/struct.Foo.html#method.new`
+// has segments `["0.1.0", "foo", "a", "struct.Foo.html"]` and fragment `method.new`.
+//
+// Segments and fragment are percent-decoded.
+#[derive(Debug, PartialEq)]
+pub struct DocsUrl {
+    pub layout: DocsLayout,
+    pub kind: DocsUrlKind,
+    // path segments after base URL and package name, without empty last segment
+    pub segments: Vec<String>,
+    // query without `?`, e.g. `search=foo`
+    pub query: Option<String>,
+    pub fragment: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DocsUrlKind {
+    // rustdoc page, e.g. `https://docs.rs/foo/0.1.0/foo/struct.Foo.html`
+    Rustdoc,
+    // `docs.rs` crate page, e.g. `https://docs.rs/crate/foo/0.1.0/features`
+    CratePage,
+    // rustdoc source page, e.g. `https://docs.rs/foo/0.1.0/src/foo/lib.rs.html#10-20`
+    Source,
+}
+
+impl DocsUrl {
+    // Parses URL of documentation of given package,
+    // returning `None` if URL is not under any of given base URLs.
+    //
+    // With layout `DocsLayout::CargoDoc` base URL can contain documentation
+    // of several crates, so URL must also have given crate name,
+    // or package name with `-` replaced by `_` if not given.
+    pub fn parse(
+        url: &str,
+        package_name: &str,
+        crate_name: Option<&str>,
+        base_urls: &[(String, DocsLayout)],
+    ) -> Option<Self> {
+        let crate_name = match crate_name {
+            Some(crate_name) => crate_name.to_owned(),
+            None => package_name.replace('-', "_"),
+        };
+        base_urls
+            .iter()
+            .find_map(|(base_url, layout)| Self::parse_with(url, package_name, base_url, *layout))
+            .filter(|docs_url| {
+                let url_crate = match docs_url.kind {
+                    DocsUrlKind::Source => docs_url.crate_path().get(1),
+                    _ => docs_url.crate_path().first(),
+                };
+                docs_url.layout != DocsLayout::CargoDoc || url_crate == Some(&crate_name)
+            })
+    }
+
+    fn parse_with(
+        url: &str,
+        package_name: &str,
+        base_url: &str,
+        layout: DocsLayout,
+    ) -> Option<Self> {
+        let mut rest = url.strip_prefix(base_url)?;
+        let mut kind = DocsUrlKind::Rustdoc;
+        if layout == DocsLayout::DocsRs {
+            rest = rest.strip_prefix('/')?;
+            if let Some(crate_page) = rest.strip_prefix("crate/") {
+                rest = crate_page;
+                kind = DocsUrlKind::CratePage;
+            }
+            rest = rest.strip_prefix(package_name)?;
+        }
+
+        // optional `/` after prefix
+        let rest = match rest.as_bytes().first() {
+            None | Some(b'#') | Some(b'?') => rest,
+            Some(b'/') => &rest[1..],
+            Some(_) => return None,
+        };
+
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(percent_decode(fragment))),
+            None => (rest, None),
+        };
+        let (path, query) = match rest.split_once('?') {
+            Some((path, query)) => (path, Some(query.to_owned())),
+            None => (rest, None),
+        };
+
+        let mut segments: Vec<String> = if path.is_empty() {
+            Vec::new()
+        } else {
+            path.split('/').map(percent_decode).collect()
+        };
+        if segments.last().is_some_and(String::is_empty) {
+            segments.pop();
+        }
+
+        let mut docs_url = Self {
+            layout,
+            kind,
+            segments,
+            query,
+            fragment,
+        };
+        if kind == DocsUrlKind::Rustdoc && docs_url.crate_path().first().is_some_and(|s| s == "src")
+        {
+            docs_url.kind = DocsUrlKind::Source;
+        }
+        Some(docs_url)
+    }
+
+    // Returns version in URL, or `None` if URL has no version or layout has no versions.
+    pub fn version(&self) -> Option<&str> {
+        if self.layout.has_version() {
+            self.segments.first().map(String::as_str)
+        } else {
+            None
+        }
+    }
+
+    // Returns path segments after version, i.e. crate name, modules and filename,
+    // or `src`, crate name and path of source file.
+    pub fn crate_path(&self) -> &[String] {
+        if self.layout.has_version() {
+            self.segments.get(1..).unwrap_or_default()
+        } else {
+            &self.segments
+        }
+    }
+}
+
+// ======================================================================
+// FUNCTIONS - PUBLIC
+
+// Returns kind and name of item of rustdoc item page like `struct.Foo.html`,
+// or `None` if filename isn't a supported item page.
+pub fn item_page(filename: &str) -> Option<(ItemKind, &str)> {
+    let stem = filename.strip_suffix(".html")?;
+    ITEM_PAGES.iter().find_map(|(kind, prefix, _)| {
+        stem.strip_prefix(prefix)?
+            .strip_prefix('.')
+            .filter(|name| !name.is_empty())
+            .map(|name| (*kind, name))
+    })
+}
+
+// Returns filename prefix of page of item with given kind, e.g. `struct`,
+// and for associated items also fragment prefix, e.g. `method`.
+//
+// Returns `None` for modules, which have no item page.
+pub fn item_page_prefixes(kind: ItemKind) -> Option<(&'static str, Option<&'static str>)> {
+    ITEM_PAGES
+        .iter()
+        .find_map(|(page_kind, prefix, associated)| {
+            if *page_kind == kind {
+                Some((*prefix, None))
+            } else {
+                associated
+                    .iter()
+                    .find(|(associated_kind, _)| *associated_kind == kind)
+                    .map(|(_, fragment_prefix)| (*prefix, Some(*fragment_prefix)))
+            }
+        })
+}
+
+// Returns intra-doc path of module, or item if given, with fragment,
+// e.g. `crate::a::Foo::new` for modules `["a"]`, item `Foo` and fragment `method.new`.
+//
+// Fragments of associated items like methods become part of the path.
+// Other fragments are percent-encoded, since the fragment is percent-decoded.
+// With `disambiguate` path has disambiguator like `method@`, except crate root.
+pub fn intra_doc_path(
+    modules: &[String],
+    item: Option<(ItemKind, &str)>,
+    fragment: Option<&str>,
+    disambiguate: bool,
+) -> String {
+    let mut kind = ItemKind::Module;
+    let mut path = "crate".to_owned();
+    for module in modules {
+        path.push_str("::");
+        path.push_str(module);
+    }
+    let mut fragment = fragment;
+    if let Some((item_kind, name)) = item {
+        kind = item_kind;
+        path.push_str("::");
+        path.push_str(name);
+        let associated = ITEM_PAGES
+            .iter()
+            .find(|(page_kind, _, _)| *page_kind == item_kind)
+            .map_or(&[][..], |(_, _, associated)| associated);
+        let associated_item = fragment.and_then(|fragment| {
+            associated
+                .iter()
+                .find_map(|(associated_kind, fragment_prefix)| {
+                    fragment
+                        .strip_prefix(fragment_prefix)
+                        .and_then(|rest| rest.strip_prefix('.'))
+                        .filter(|name| intra_doc_links::is_identifier(name))
+                        .map(|name| (*associated_kind, name))
+                })
+        });
+        if let Some((associated_kind, associated_name)) = associated_item {
+            kind = associated_kind;
+            path.push_str("::");
+            path.push_str(associated_name);
+            fragment = None;
+        }
+    }
+    if disambiguate && path != "crate" {
+        path.insert(0, '@');
+        path.insert_str(0, disambiguator(kind));
+    }
+    if let Some(fragment) = fragment {
+        path.push('#');
+        path.push_str(&percent_encode(fragment, true));
+    }
+    path
+}
+
+// Encodes characters which aren't allowed in path segment or fragment of URL
+// as `%XX` escapes, so that `percent_decode` returns the original text.
+//
+// `/` and `?` are allowed only in fragments.
+pub fn percent_encode(text: &str, is_fragment: bool) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric()
+            || b"-._~!$&'()*+,;=:@".contains(&byte)
+            || (is_fragment && b"/?".contains(&byte))
+        {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+// Returns intra-doc disambiguator of item kind, e.g. `struct` of `struct@crate::Foo`.
+fn disambiguator(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Enum => "enum",
+        ItemKind::EnumMethod | ItemKind::StructMethod | ItemKind::TraitMethod => "method",
+        ItemKind::EnumVariant => "variant",
+        ItemKind::Function => "fn",
+        ItemKind::Macro => "macro",
+        ItemKind::Module => "mod",
+        ItemKind::Struct => "struct",
+        ItemKind::Trait => "trait",
+    }
+}
+
+// Decodes `%XX` escapes, keeping text as-is if result isn't valid UTF-8.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| text.to_owned())
+}
+
+// ======================================================================
+// DATA
+
+// Associated item kinds with fragment prefixes, e.g. `method` of `#method.new`.
+type AssociatedItems = &'static [(ItemKind, &'static str)];
+
+// Item kinds with item pages, their filename prefixes,
+// and associated item kinds with fragment prefixes.
+//
+// These are the item pages which are converted to intra-doc links.
+const ITEM_PAGES: &[(ItemKind, &str, AssociatedItems)] = &[
+    (
+        ItemKind::Enum,
+        "enum",
+        &[
+            (ItemKind::EnumMethod, "method"),
+            (ItemKind::EnumVariant, "variant"),
+        ],
+    ),
+    (ItemKind::Function, "fn", &[]),
+    (ItemKind::Macro, "macro", &[]),
+    (
+        ItemKind::Struct,
+        "struct",
+        &[(ItemKind::StructMethod, "method")],
+    ),
+    (
+        ItemKind::Trait,
+        "trait",
+        &[(ItemKind::TraitMethod, "tymethod")],
+    ),
+];
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(url: &str) -> Option<DocsUrl> {
+        let base_urls = [
+            ("https://docs.rs".to_owned(), DocsLayout::DocsRs),
+            (
+                "https://docs.example.com/foo".to_owned(),
+                DocsLayout::Versioned,
+            ),
+            ("http://localhost:8000".to_owned(), DocsLayout::CargoDoc),
+        ];
+        DocsUrl::parse(url, "foo", None, &base_urls)
+    }
+
+    #[test]
+    fn docs_rs() {
+        let url = parse("https://docs.rs/foo/0.1.0/foo/a/struct.A.html#method.new").unwrap();
+        assert_eq!(url.segments, ["0.1.0", "foo", "a", "struct.A.html"]);
+        assert_eq!(url.fragment, Some("method.new".to_owned()));
+        assert_eq!(url.version(), Some("0.1.0"));
+        assert_eq!(url.crate_path(), ["foo", "a", "struct.A.html"]);
+    }
+
+    #[test]
+    fn docs_rs_root() {
+        for url in ["https://docs.rs/foo", "https://docs.rs/foo/"] {
+            let url = parse(url).unwrap();
+            assert_eq!(url.version(), None);
+            assert_eq!(url.crate_path(), [""; 0]);
+        }
+        assert_eq!(
+            parse("https://docs.rs/foo#x").unwrap().fragment.as_deref(),
+            Some("x")
+        );
+    }
+
+    #[test]
+    fn other_packages() {
+        assert_eq!(parse("https://docs.rs/foobar"), None);
+        assert_eq!(parse("https://docs.rs/bar/0.1.0/bar"), None);
+        assert_eq!(parse("https://docs.example.com/foobar"), None);
+    }
+
+    #[test]
+    fn query() {
+        let url = parse("https://docs.rs/foo/0.1.0/foo/?search=a#x").unwrap();
+        assert_eq!(url.segments, ["0.1.0", "foo"]);
+        assert_eq!(url.query.as_deref(), Some("search=a"));
+        assert_eq!(url.fragment.as_deref(), Some("x"));
+        assert_eq!(
+            parse("https://docs.rs/foo?search=a").unwrap().segments,
+            [""; 0]
+        );
+    }
+
+    #[test]
+    fn percent_encoding() {
+        let url = parse("https://docs.rs/foo/%5E0.1/foo/struct.A%C3%A4.html#%2A%zz").unwrap();
+        assert_eq!(url.segments, ["^0.1", "foo", "struct.Aä.html"]);
+        assert_eq!(url.fragment.as_deref(), Some("*%zz"));
+        assert_eq!(percent_decode("%FF%"), "%FF%");
+    }
+
+    #[test]
+    fn percent_encoding_round_trip() {
+        assert_eq!(percent_encode("^0.1", false), "%5E0.1");
+        assert_eq!(percent_encode("a/b?c", false), "a%2Fb%3Fc");
+        assert_eq!(percent_encode("a/b?c", true), "a/b?c");
+        assert_eq!(
+            percent_encode("impl-From<&str>-for-Aä #%", true),
+            "impl-From%3C&str%3E-for-A%C3%A4%20%23%25"
+        );
+        for text in ["*", "a%zz", "%FF", "<T> #ä"] {
+            assert_eq!(percent_decode(&percent_encode(text, false)), text);
+            assert_eq!(percent_decode(&percent_encode(text, true)), text);
+        }
+    }
+
+    #[test]
+    fn crate_page() {
+        let url = parse("https://docs.rs/crate/foo/latest/features").unwrap();
+        assert_eq!(url.kind, DocsUrlKind::CratePage);
+        assert_eq!(url.version(), Some("latest"));
+        assert_eq!(parse("https://docs.rs/crate/foobar/latest"), None);
+    }
+
+    #[test]
+    fn source() {
+        let url = parse("https://docs.rs/foo/0.1.0/src/foo/a/b.rs.html#10-20").unwrap();
+        assert_eq!(url.kind, DocsUrlKind::Source);
+        assert_eq!(url.crate_path(), ["src", "foo", "a", "b.rs.html"]);
+        assert_eq!(url.fragment, Some("10-20".to_owned()));
+
+        let url = parse("http://localhost:8000/src/foo/lib.rs.html").unwrap();
+        assert_eq!(url.kind, DocsUrlKind::Source);
+        assert_eq!(parse("http://localhost:8000/src/bar/lib.rs.html"), None);
+    }
+
+    #[test]
+    fn versioned() {
+        let url = parse("https://docs.example.com/foo/0.1.0/foo/fn.a.html").unwrap();
+        assert_eq!(url.version(), Some("0.1.0"));
+        assert_eq!(url.crate_path(), ["foo", "fn.a.html"]);
+    }
+
+    #[test]
+    fn item_pages() {
+        assert_eq!(
+            item_page("struct.Foo.html"),
+            Some((ItemKind::Struct, "Foo"))
+        );
+        assert_eq!(item_page("fn.foo.html"), Some((ItemKind::Function, "foo")));
+        assert_eq!(item_page("struct..html"), None);
+        assert_eq!(item_page("constant.FOO.html"), None);
+        assert_eq!(item_page("struct.Foo"), None);
+        assert_eq!(item_page_prefixes(ItemKind::Enum), Some(("enum", None)));
+        assert_eq!(
+            item_page_prefixes(ItemKind::TraitMethod),
+            Some(("trait", Some("tymethod")))
+        );
+        assert_eq!(item_page_prefixes(ItemKind::Module), None);
+    }
+
+    #[test]
+    fn intra_doc_paths() {
+        let modules = ["a".to_owned(), "b".to_owned()];
+        assert_eq!(intra_doc_path(&[], None, None, false), "crate");
+        assert_eq!(
+            intra_doc_path(&modules, None, Some("x"), false),
+            "crate::a::b#x"
+        );
+        assert_eq!(
+            intra_doc_path(
+                &modules,
+                Some((ItemKind::Enum, "A")),
+                Some("variant.B"),
+                false
+            ),
+            "crate::a::b::A::B"
+        );
+        assert_eq!(
+            intra_doc_path(&[], Some((ItemKind::Struct, "A")), Some("variant.B"), false),
+            "crate::A#variant.B"
+        );
+        assert_eq!(
+            intra_doc_path(&[], Some((ItemKind::Function, "f")), None, false),
+            "crate::f"
+        );
+    }
+
+    #[test]
+    fn disambiguated_intra_doc_paths() {
+        let modules = ["a".to_owned()];
+        let path = |item, fragment| intra_doc_path(&modules, item, fragment, true);
+        assert_eq!(intra_doc_path(&[], None, Some("x"), true), "crate#x");
+        assert_eq!(path(None, Some("x")), "mod@crate::a#x");
+        assert_eq!(
+            path(Some((ItemKind::Macro, "m")), None),
+            "macro@crate::a::m"
+        );
+        assert_eq!(
+            path(Some((ItemKind::Enum, "A")), Some("variant.B")),
+            "variant@crate::a::A::B"
+        );
+        assert_eq!(
+            path(Some((ItemKind::Trait, "T")), Some("tymethod.f")),
+            "method@crate::a::T::f"
+        );
+        assert_eq!(
+            path(Some((ItemKind::Struct, "S")), Some("impl-Clone-for-S")),
+            "struct@crate::a::S#impl-Clone-for-S"
+        );
+        assert_eq!(
+            path(Some((ItemKind::Struct, "S")), Some("method.a b")),
+            "struct@crate::a::S#method.a%20b"
+        );
+        assert_eq!(path(None, Some("größe")), "mod@crate::a#gr%C3%B6%C3%9Fe");
+    }
+
+    #[test]
+    fn cargo_doc() {
+        let url = parse("http://localhost:8000/foo/fn.a.html").unwrap();
+        assert_eq!(url.version(), None);
+        assert_eq!(url.crate_path(), ["foo", "fn.a.html"]);
+        assert_eq!(parse("http://localhost:8000/bar/fn.a.html"), None);
+        assert_eq!(parse("http://localhost:8000/"), None);
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::Error;
+
+// ======================================================================
+// EMBED - PUBLIC
+
+#[derive(Debug, PartialEq)]
+pub struct Embed {
+    pub path: String,
+    pub region: Option<String>,
+}
+
+impl Embed {
+    // <!-- rustdocify:embed examples/basic.rs region=setup -->
+    //
+    // Returns `None` if this is not an embed marker line.
+    pub fn from_marker(line: &str) -> Option<Self> {
+        let args = line
+            .trim()
+            .strip_prefix("<!-- rustdocify:embed")?
+            .strip_suffix("-->")?;
+        if !args.starts_with(char::is_whitespace) {
+            return None;
+        }
+
+        let mut args = args.split_whitespace();
+        let path = args.next()?.to_owned();
+        let region = match args.next() {
+            Some(arg) => Some(arg.strip_prefix("region=")?.to_owned()),
+            None => None,
+        };
+
+        if args.next().is_some() {
+            None
+        } else {
+            Some(Self { path, region })
+        }
+    }
+
+    // Returns name of embedded file and region, e.g. `examples/basic.rs:setup`.
+    pub fn name(&self) -> String {
+        match &self.region {
+            Some(region) => format!("{}:{}", self.path, region),
+            None => self.path.clone(),
+        }
+    }
+
+    // Reads embedded file or region relative to `base_dir`,
+    // removing anchor lines like `// ANCHOR: name`.
+    //
+    // Returned content has `\n` line endings and ends with `\n`, unless empty.
+    pub fn read(&self, base_dir: Option<&Path>) -> Result<String, Error> {
+        let text = fs::read_to_string(self.full_path(base_dir))
+            .map_err(|error| Error::EmbedFailed(format!("{}: {}", self.path, error)))?;
+
+        match &self.region {
+            Some(region) => extract_region(&text, region)
+                .ok_or_else(|| Error::EmbedFailed(format!("{}: region not found", self.name()))),
+            None => Ok(normalize(text.lines().filter(|line| !is_anchor(line)))),
+        }
+    }
+
+    // Returns path of embedded file relative to `base_dir`.
+    pub fn full_path(&self, base_dir: Option<&Path>) -> PathBuf {
+        match base_dir {
+            Some(base_dir) => base_dir.join(&self.path),
+            None => PathBuf::from(&self.path),
+        }
+    }
+
+    // Returns language of code block for embedded file.
+    pub fn language(&self) -> &str {
+        match Path::new(&self.path).extension().and_then(|e| e.to_str()) {
+            Some("rs") => "rust",
+            Some("toml") => "toml",
+            Some("md") => "markdown",
+            Some("json") => "json",
+            Some("sh") => "sh",
+            _ => "text",
+        }
+    }
+}
+
+// ======================================================================
+// FUNCTIONS - PUBLIC
+
+// Returns code block with given info string and content,
+// with enough backticks that content can't end the code block.
+pub fn code_block(info: &str, content: &str) -> Vec<String> {
+    let backtick_count = content
+        .lines()
+        .map(|line| line.len() - line.trim_start_matches('`').len())
+        .max()
+        .unwrap_or(0)
+        .max(2)
+        + 1;
+    let fence = "`".repeat(backtick_count);
+
+    let mut lines = vec![format!("{}{}\n", fence, info)];
+    lines.extend(content.lines().map(|line| format!("{}\n", line)));
+    lines.push(format!("{}\n", fence));
+    lines
+}
+
+// Returns `true` if readme copy of embedded content matches given content.
+pub fn is_up_to_date(copy: &str, content: &str) -> bool {
+    normalize(copy.lines()) == content
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+// Returns lines between `ANCHOR: region` and `ANCHOR_END: region`
+// with common indentation and other anchor lines removed.
+fn extract_region(text: &str, region: &str) -> Option<String> {
+    let mut lines = text.lines();
+    lines.find(|line| anchor_name(line, "ANCHOR:") == Some(region))?;
+
+    let mut region_lines = Vec::new();
+    for line in lines {
+        if anchor_name(line, "ANCHOR_END:") == Some(region) {
+            let indent = region_lines
+                .iter()
+                .filter(|line: &&&str| !line.trim().is_empty())
+                .map(|line| line.len() - line.trim_start().len())
+                .min()
+                .unwrap_or(0);
+            return Some(normalize(
+                region_lines
+                    .iter()
+                    .map(|line| line.get(indent..).unwrap_or("")),
+            ));
+        }
+        if !is_anchor(line) {
+            region_lines.push(line);
+        }
+    }
+
+    None
+}
+
+fn is_anchor(line: &str) -> bool {
+    anchor_name(line, "ANCHOR:").is_some() || anchor_name(line, "ANCHOR_END:").is_some()
+}
+
+// Returns name of anchor if line is like `// ANCHOR: name`.
+fn anchor_name<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+    let pos = line.find(keyword)?;
+    Some(line[pos + keyword.len()..].trim()).filter(|name| !name.is_empty())
+}
+
+fn normalize<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    let mut result = String::new();
+    for line in lines {
+        result.push_str(line.trim_end());
+        result.push('\n');
+    }
+    result
+}
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markers() {
+        assert_eq!(
+            Embed::from_marker("<!-- rustdocify:embed examples/a.rs region=setup -->\n"),
+            Some(Embed {
+                path: "examples/a.rs".to_owned(),
+                region: Some("setup".to_owned())
+            })
+        );
+        assert_eq!(
+            Embed::from_marker("<!-- rustdocify:embed a.rs -->"),
+            Some(Embed {
+                path: "a.rs".to_owned(),
+                region: None
+            })
+        );
+        assert_eq!(Embed::from_marker("<!-- rustdocify:embed -->"), None);
+        assert_eq!(
+            Embed::from_marker("<!-- rustdocify:embed a.rs foo -->"),
+            None
+        );
+    }
+
+    #[test]
+    fn regions() {
+        let text = concat!(
+            "fn main() {\n",
+            "    // ANCHOR: all\n",
+            "    // ANCHOR: setup\n",
+            "    let a = 1;\n",
+            "\n",
+            "        let b = 2;\n",
+            "    // ANCHOR_END: setup\n",
+            "    // ANCHOR_END: all\n",
+            "}\n",
+        );
+        assert_eq!(
+            extract_region(text, "setup"),
+            Some("let a = 1;\n\n    let b = 2;\n".to_owned())
+        );
+        assert_eq!(
+            extract_region(text, "all"),
+            Some("let a = 1;\n\n    let b = 2;\n".to_owned())
+        );
+        assert_eq!(extract_region(text, "foo"), None);
+    }
+
+    #[test]
+    fn code_blocks() {
+        assert_eq!(code_block("rust", "a\n"), ["```rust\n", "a\n", "```\n"]);
+        assert_eq!(
+            code_block("markdown", "```\n"),
+            ["````markdown\n", "```\n", "````\n"]
+        );
+    }
+
+    #[test]
+    fn up_to_date() {
+        assert!(is_up_to_date("a  \r\nb\r\n", "a\nb\n"));
+        assert!(!is_up_to_date("a\nc\n", "a\nb\n"));
+    }
+
+    #[test]
+    fn missing_file() {
+        let embed = Embed::from_marker("<!-- rustdocify:embed no/such/file.rs -->").unwrap();
+        assert!(matches!(embed.read(None), Err(Error::EmbedFailed(_))));
+    }
+}
@@ -0,0 +1,375 @@
+use super::{code_span_len, link_definitions};
+
+// ======================================================================
+// FUNCTIONS - PUBLIC
+
+// Replaces known GitHub emoji shortcodes like `:rocket:` with emojis,
+// except within code spans, inline link destinations, autolinks and HTML tags.
+pub fn expand_shortcodes(line: &str) -> String {
+    let bytes = line.as_bytes();
+    let mut result = String::with_capacity(line.len());
+    let mut pos = 0;
+
+    while pos < line.len() {
+        match bytes[pos] {
+            b'`' => {
+                let code_span_len = code_span_len(&line[pos..]);
+                result.push_str(&line[pos..pos + code_span_len]);
+                pos += code_span_len;
+            }
+            b']' if line[pos..].starts_with("](") => {
+                let len =
+                    2 + link_definitions::inline_destination_len(&line[pos + 2..]).unwrap_or(0);
+                result.push_str(&line[pos..pos + len]);
+                pos += len;
+            }
+            b'<' => {
+                let len = tag_len(&line[pos..]).unwrap_or(1);
+                result.push_str(&line[pos..pos + len]);
+                pos += len;
+            }
+            b':' => {
+                let name_len = line[pos + 1..]
+                    .find(|c: char| !is_shortcode_char(c))
+                    .unwrap_or(line.len() - pos - 1);
+                let name = &line[pos + 1..pos + 1 + name_len];
+                let emoji = if line[pos + 1 + name_len..].starts_with(':') {
+                    lookup(name)
+                } else {
+                    None
+                };
+                if let Some(emoji) = emoji {
+                    result.push_str(emoji);
+                    pos += name_len + 2;
+                } else {
+                    result.push(':');
+                    pos += 1;
+                }
+            }
+            _ => {
+                let len = line[pos + 1..]
+                    .find(['`', ':', ']', '<'])
+                    .map_or(line.len() - pos, |offset| offset + 1);
+                result.push_str(&line[pos..pos + len]);
+                pos += len;
+            }
+        }
+    }
+
+    result
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+fn is_shortcode_char(c: char) -> bool {
+    c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '+' || c == '-'
+}
+
+// Returns length of autolink like `<https://...>` or HTML tag like `<a href="...">`
+// at start of text, or `None` if there is none.
+fn tag_len(text: &str) -> Option<usize> {
+    let rest = text.strip_prefix('<')?;
+    let scheme_len = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '+' || c == '.' || c == '-'))
+        .unwrap_or(rest.len());
+    if (2..=32).contains(&scheme_len)
+        && rest.starts_with(|c: char| c.is_ascii_alphabetic())
+        && rest[scheme_len..].starts_with(':')
+    {
+        let len = rest.find(|c: char| c == '<' || c == '>' || c.is_ascii_whitespace())?;
+        return Some(len + 2).filter(|_| rest[len..].starts_with('>'));
+    }
+
+    if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!' || c == '?') {
+        return None;
+    }
+    let mut quote = None;
+    for (pos, c) in rest.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(pos + 2),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn lookup(name: &str) -> Option<&'static str> {
+    EMOJIS
+        .binary_search_by(|(shortcode, _)| shortcode.cmp(&name))
+        .ok()
+        .map(|index| EMOJIS[index].1)
+}
+
+// ======================================================================
+// DATA
+
+// GitHub emoji shortcodes, sorted by shortcode.
+const EMOJIS: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("1234", "🔢"),
+    ("alarm_clock", "⏰"),
+    ("ambulance", "🚑"),
+    ("anchor", "⚓"),
+    ("angry", "😠"),
+    ("apple", "🍎"),
+    ("arrow_down", "⬇️"),
+    ("arrow_left", "⬅️"),
+    ("arrow_right", "➡️"),
+    ("arrow_up", "⬆️"),
+    ("art", "🎨"),
+    ("baby", "👶"),
+    ("balloon", "🎈"),
+    ("bang", "❗"),
+    ("bangbang", "‼️"),
+    ("battery", "🔋"),
+    ("beer", "🍺"),
+    ("beers", "🍻"),
+    ("beetle", "🐞"),
+    ("bell", "🔔"),
+    ("bomb", "💣"),
+    ("book", "📖"),
+    ("bookmark", "🔖"),
+    ("books", "📚"),
+    ("boom", "💥"),
+    ("bow", "🙇"),
+    ("brain", "🧠"),
+    ("bricks", "🧱"),
+    ("broken_heart", "💔"),
+    ("bug", "🐛"),
+    ("bulb", "💡"),
+    ("bust_in_silhouette", "👤"),
+    ("busts_in_silhouette", "👥"),
+    ("cake", "🍰"),
+    ("calendar", "📆"),
+    ("camera", "📷"),
+    ("card_file_box", "🗃️"),
+    ("chart_with_downwards_trend", "📉"),
+    ("chart_with_upwards_trend", "📈"),
+    ("check", "✔️"),
+    ("checkered_flag", "🏁"),
+    ("clap", "👏"),
+    ("clipboard", "📋"),
+    ("clock1", "🕐"),
+    ("closed_lock_with_key", "🔐"),
+    ("cloud", "☁️"),
+    ("coffee", "☕"),
+    ("collision", "💥"),
+    ("computer", "💻"),
+    ("confetti_ball", "🎊"),
+    ("construction", "🚧"),
+    ("construction_worker", "👷"),
+    ("cookie", "🍪"),
+    ("crab", "🦀"),
+    ("cry", "😢"),
+    ("crystal_ball", "🔮"),
+    ("dart", "🎯"),
+    ("dash", "💨"),
+    ("desktop_computer", "🖥️"),
+    ("dizzy", "💫"),
+    ("dog", "🐶"),
+    ("dollar", "💵"),
+    ("door", "🚪"),
+    ("egg", "🥚"),
+    ("email", "📧"),
+    ("envelope", "✉️"),
+    ("exclamation", "❗"),
+    ("eyes", "👀"),
+    ("fast_forward", "⏩"),
+    ("file_folder", "📁"),
+    ("fire", "🔥"),
+    ("fireworks", "🎆"),
+    ("floppy_disk", "💾"),
+    ("gear", "⚙️"),
+    ("gem", "💎"),
+    ("ghost", "👻"),
+    ("gift", "🎁"),
+    ("globe_with_meridians", "🌐"),
+    ("goal_net", "🥅"),
+    ("grey_exclamation", "❕"),
+    ("grey_question", "❔"),
+    ("grin", "😁"),
+    ("grinning", "😀"),
+    ("hammer", "🔨"),
+    ("hammer_and_wrench", "🛠️"),
+    ("hand", "✋"),
+    ("handshake", "🤝"),
+    ("hash", "#️⃣"),
+    ("heart", "❤️"),
+    ("heavy_check_mark", "✔️"),
+    ("heavy_minus_sign", "➖"),
+    ("heavy_multiplication_x", "✖️"),
+    ("heavy_plus_sign", "➕"),
+    ("hourglass", "⌛"),
+    ("house", "🏠"),
+    ("hugs", "🤗"),
+    ("information_source", "ℹ️"),
+    ("jigsaw", "🧩"),
+    ("joy", "😂"),
+    ("key", "🔑"),
+    ("keyboard", "⌨️"),
+    ("label", "🏷️"),
+    ("laptop", "💻"),
+    ("laughing", "😆"),
+    ("link", "🔗"),
+    ("lipstick", "💄"),
+    ("lock", "🔒"),
+    ("loud_sound", "🔊"),
+    ("mag", "🔍"),
+    ("mag_right", "🔎"),
+    ("mailbox", "📫"),
+    ("memo", "📝"),
+    ("microscope", "🔬"),
+    ("money_with_wings", "💸"),
+    ("monocle_face", "🧐"),
+    ("muscle", "💪"),
+    ("mute", "🔇"),
+    ("negative_squared_cross_mark", "❎"),
+    ("new", "🆕"),
+    ("no_entry", "⛔"),
+    ("no_entry_sign", "🚫"),
+    ("notebook", "📓"),
+    ("ok", "🆗"),
+    ("ok_hand", "👌"),
+    ("open_book", "📖"),
+    ("package", "📦"),
+    ("page_facing_up", "📄"),
+    ("paperclip", "📎"),
+    ("partying_face", "🥳"),
+    ("pencil", "📝"),
+    ("pencil2", "✏️"),
+    ("point_down", "👇"),
+    ("point_left", "👈"),
+    ("point_right", "👉"),
+    ("point_up", "☝️"),
+    ("poop", "💩"),
+    ("pray", "🙏"),
+    ("pushpin", "📌"),
+    ("question", "❓"),
+    ("rainbow", "🌈"),
+    ("raised_hands", "🙌"),
+    ("recycle", "♻️"),
+    ("red_circle", "🔴"),
+    ("repeat", "🔁"),
+    ("rewind", "⏪"),
+    ("robot", "🤖"),
+    ("rocket", "🚀"),
+    ("rotating_light", "🚨"),
+    ("scroll", "📜"),
+    ("see_no_evil", "🙈"),
+    ("seedling", "🌱"),
+    ("shield", "🛡️"),
+    ("shipit", "🐿️"),
+    ("shrug", "🤷"),
+    ("skull", "💀"),
+    ("sleeping", "😴"),
+    ("slightly_smiling_face", "🙂"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("smirk", "😏"),
+    ("snail", "🐌"),
+    ("snake", "🐍"),
+    ("snowflake", "❄️"),
+    ("soon", "🔜"),
+    ("sos", "🆘"),
+    ("sparkles", "✨"),
+    ("speech_balloon", "💬"),
+    ("star", "⭐"),
+    ("star2", "🌟"),
+    ("stars", "🌠"),
+    ("stop_sign", "🛑"),
+    ("sunglasses", "😎"),
+    ("sunny", "☀️"),
+    ("sweat_smile", "😅"),
+    ("tada", "🎉"),
+    ("test_tube", "🧪"),
+    ("thinking", "🤔"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("ticket", "🎫"),
+    ("toolbox", "🧰"),
+    ("trophy", "🏆"),
+    ("truck", "🚚"),
+    ("turtle", "🐢"),
+    ("twisted_rightwards_arrows", "🔀"),
+    ("umbrella", "☔"),
+    ("unlock", "🔓"),
+    ("v", "✌️"),
+    ("warning", "⚠️"),
+    ("wastebasket", "🗑️"),
+    ("wave", "👋"),
+    ("white_check_mark", "✅"),
+    ("wink", "😉"),
+    ("wrench", "🔧"),
+    ("x", "❌"),
+    ("zap", "⚡"),
+    ("zzz", "💤"),
+];
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emojis_are_sorted() {
+        assert!(EMOJIS.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn known_shortcodes() {
+        assert_eq!(
+            expand_shortcodes(":rocket: Fast :+1::white_check_mark:\n"),
+            "\u{1f680} Fast \u{1f44d}\u{2705}\n"
+        );
+    }
+
+    #[test]
+    fn unknown_shortcodes() {
+        assert_eq!(
+            expand_shortcodes("a :foo: b:: https://x 10:30:00 :"),
+            "a :foo: b:: https://x 10:30:00 :"
+        );
+    }
+
+    #[test]
+    fn code_spans() {
+        assert_eq!(
+            expand_shortcodes(":zap: `:zap:` ``a`:zap:`` ``:zap:"),
+            "\u{26a1} `:zap:` ``a`:zap:`` ``\u{26a1}"
+        );
+    }
+
+    #[test]
+    fn link_destinations() {
+        assert_eq!(
+            expand_shortcodes("[:zap:](https://x.com/:rocket:/) [a]( <b/:zap:> \":zap:\")"),
+            "[\u{26a1}](https://x.com/:rocket:/) [a]( <b/:zap:> \"\u{26a1}\")"
+        );
+        assert_eq!(expand_shortcodes("a]( :zap:"), "a]( \u{26a1}");
+    }
+
+    #[test]
+    fn autolinks() {
+        assert_eq!(
+            expand_shortcodes("<https://x.com/:rocket:/> :zap: < :zap: >"),
+            "<https://x.com/:rocket:/> \u{26a1} < \u{26a1} >"
+        );
+    }
+
+    #[test]
+    fn html_tags() {
+        assert_eq!(
+            expand_shortcodes("<img alt=\":zap:>\" src=\"x/:zap:/\"> :zap: </a> <!-- :zap: -->"),
+            "<img alt=\":zap:>\" src=\"x/:zap:/\"> \u{26a1} </a> <!-- :zap: -->"
+        );
+        assert_eq!(expand_shortcodes("a <b :zap:"), "a <b \u{26a1}");
+    }
+}
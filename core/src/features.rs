@@ -0,0 +1,116 @@
+use super::{
+    toml::{Toml, Value},
+    Error,
+};
+
+// ======================================================================
+// FUNCTIONS - PUBLIC
+
+// <!-- rustdocify:features -->
+pub fn is_features_marker(line: &str) -> bool {
+    line.trim() == "<!-- rustdocify:features -->"
+}
+
+// Returns markdown table of features in `[features]` of given `Cargo.toml`.
+//
+// Table has no trailing newline.
+pub fn features_table(manifest: &str) -> Result<String, Error> {
+    let toml = Toml::parse(manifest).map_err(Error::InvalidManifest)?;
+
+    let mut result = String::from("| Feature | Enables | Description |\n|---|---|---|");
+    let mut is_empty = true;
+
+    for entry in toml.table(&["features"]) {
+        let enables = match entry.value.as_array() {
+            Some(array) => array
+                .iter()
+                .map(|value| match value {
+                    Value::String(s) => Ok(format!("`{}`", s)),
+                    _ => Err(Error::InvalidManifest(format!(
+                        "line {}: feature `{}` has non-string value",
+                        entry.line,
+                        entry.key.join(".")
+                    ))),
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .join(", "),
+            None => {
+                return Err(Error::InvalidManifest(format!(
+                    "line {}: feature `{}` is not an array",
+                    entry.line,
+                    entry.key.join(".")
+                )))
+            }
+        };
+
+        result.push_str(&format!(
+            "\n| `{}` | {} | {} |",
+            entry.key.join("."),
+            enables,
+            entry.comment.replace('|', "\\|")
+        ));
+        is_empty = false;
+    }
+
+    if is_empty {
+        Ok("This package has no features.".to_owned())
+    } else {
+        Ok(result)
+    }
+}
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn features() {
+        let manifest = concat!(
+            "[package]\nname = \"foo\"\n\n",
+            "[features]\n",
+            "default = [\"std\"]\n",
+            "# Enables `std` support.\n",
+            "std = []\n",
+            "## Serde support,\n## a | b.\n",
+            "serde = [\n  \"dep:serde\",\n  \"bar/serde\",\n]\n",
+            "\n[dependencies]\nserde = { version = \"1\", optional = true }\n",
+        );
+        assert_eq!(
+            features_table(manifest).unwrap(),
+            concat!(
+                "| Feature | Enables | Description |\n",
+                "|---|---|---|\n",
+                "| `default` | `std` |  |\n",
+                "| `std` |  | Enables `std` support. |\n",
+                "| `serde` | `dep:serde`, `bar/serde` | Serde support, a \\| b. |",
+            )
+        );
+    }
+
+    #[test]
+    fn no_features() {
+        assert_eq!(
+            features_table("[package]\nname = \"foo\"\n").unwrap(),
+            "This package has no features."
+        );
+    }
+
+    #[test]
+    fn feature_not_array() {
+        assert_eq!(
+            features_table("[features]\nfoo = \"bar\"\n"),
+            Err(Error::InvalidManifest(
+                "line 2: feature `foo` is not an array".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn marker() {
+        assert!(is_features_marker("<!-- rustdocify:features -->\r\n"));
+        assert!(!is_features_marker("`<!-- rustdocify:features -->`\n"));
+    }
+}
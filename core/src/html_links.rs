@@ -0,0 +1,436 @@
+use super::{
+    code_span_len,
+    docs_urls::{DocsUrl, DocsUrlKind},
+    DocsLayout, Error,
+};
+
+// ======================================================================
+// FUNCTIONS - PUBLIC
+
+// Returns `true` if given line starts an HTML block,
+// within which markdown is not processed.
+//
+// This is an approximation of CommonMark HTML blocks of type 6 and 7.
+pub fn is_html_block_start(line: &str) -> bool {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return false;
+    }
+    let line = line.trim();
+    let tag = match line.strip_prefix("</").or_else(|| line.strip_prefix('<')) {
+        Some(tag) => tag,
+        None => return false,
+    };
+    let name_len = tag
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(tag.len());
+    let name = tag[..name_len].to_ascii_lowercase();
+
+    if name.is_empty() {
+        false
+    } else if BLOCK_TAGS.contains(&name.as_str()) {
+        true
+    } else {
+        // Any other tag which is alone on the line.
+        line.ends_with('>') && line.find('>') == Some(line.len() - 1)
+    }
+}
+
+// Returns `true` if given line starts an HTML comment block,
+// which continues until a line containing `-->`, also over blank lines.
+pub fn is_html_comment_start(line: &str) -> bool {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    indent <= 3 && line[indent..].starts_with("<!--")
+}
+
+// Returns given line with HTML comments `<!-- ... -->` replaced with a space,
+// except within code spans, and whether the line ends within a comment.
+//
+// `is_in_comment` tells whether the line starts within a comment.
+pub fn remove_html_comments(line: &str, mut is_in_comment: bool) -> (String, bool) {
+    let mut result = String::with_capacity(line.len());
+    let mut pos = 0;
+    loop {
+        if is_in_comment {
+            match line[pos..].find("-->") {
+                Some(offset) => {
+                    result.push(' ');
+                    pos += offset + 3;
+                    is_in_comment = false;
+                }
+                None => return (result, true),
+            }
+        }
+        let start = match line[pos..].find(['`', '<']) {
+            Some(offset) => pos + offset,
+            None => {
+                result.push_str(&line[pos..]);
+                return (result, false);
+            }
+        };
+        if line[start..].starts_with("<!--") {
+            result.push_str(&line[pos..start]);
+            pos = start + 4;
+            is_in_comment = true;
+        } else {
+            let end = match line.as_bytes()[start] {
+                b'`' => start + code_span_len(&line[start..]),
+                _ => start + 1,
+            };
+            result.push_str(&line[pos..end]);
+            pos = end;
+        }
+    }
+}
+
+// Converts `<a href="https://docs.rs/PACKAGE/...">text</a>` within given line,
+// except within code spans.
+//
+// - Outside of HTML blocks anchors with only `href` attribute are
+//   converted to markdown links `[text](crate::...)`.
+// - Otherwise `href` is converted to a path relative to crate index.
+//
+// `convert_url` should check and convert URL of package documentation
+// to rustdoc format, or return other URLs unchanged.
+pub fn convert_html_links(
+    line: &str,
+    package_name: &str,
+    crate_name: Option<&str>,
+    docs_base_urls: &[(String, DocsLayout)],
+    is_in_html_block: bool,
+    mut convert_url: impl FnMut(&str) -> Result<String, Error>,
+) -> Result<String, Error> {
+    let mut result = String::with_capacity(line.len());
+    let mut pos = 0;
+
+    while let Some(offset) = line[pos..].find(['`', '<']) {
+        let start = pos + offset;
+        result.push_str(&line[pos..start]);
+
+        if line[start..].starts_with('`') {
+            let len = code_span_len(&line[start..]);
+            result.push_str(&line[start..start + len]);
+            pos = start + len;
+            continue;
+        }
+
+        let anchor = match parse_anchor(&line[start..]) {
+            Some(anchor) => anchor,
+            None => {
+                result.push('<');
+                pos = start + 1;
+                continue;
+            }
+        };
+
+        let link = convert_url(anchor.href)?;
+        if link == anchor.href {
+            // not a link to this package
+            result.push_str(&line[start..start + anchor.open_tag_len]);
+            pos = start + anchor.open_tag_len;
+        } else if let (false, true, Some(text)) =
+            (is_in_html_block, anchor.has_only_href, anchor.text)
+        {
+            result.push_str(&format!("[{}]({})", text, link));
+            pos = start + anchor.len;
+        } else {
+            let href_start = start + anchor.href_pos;
+            result.push_str(&line[start..href_start]);
+            match DocsUrl::parse(anchor.href, package_name, crate_name, docs_base_urls) {
+                Some(docs_url) if docs_url.kind == DocsUrlKind::Rustdoc => {
+                    result.push_str(&relative_path(&docs_url))
+                }
+                // e.g. link to source code in repository
+                _ => result.push_str(&link),
+            }
+            pos = href_start + anchor.href.len();
+        }
+    }
+
+    result.push_str(&line[pos..]);
+    Ok(result)
+}
+
+// ======================================================================
+// ANCHOR - PRIVATE
+
+struct Anchor<'a> {
+    href: &'a str,
+    // position of `href` value relative to `<`
+    href_pos: usize,
+    has_only_href: bool,
+    // text between `<a ...>` and `</a>`, if it can be used as markdown link text
+    text: Option<&'a str>,
+    open_tag_len: usize,
+    // length including `</a>`, or `open_tag_len` if there is no `</a>`
+    len: usize,
+}
+
+// Parses `<a ... href="..." ...>text</a>` at start of given text.
+fn parse_anchor(text: &str) -> Option<Anchor<'_>> {
+    if !text.starts_with("<a") {
+        return None;
+    }
+    let mut pos = "<a".len();
+
+    let mut href = None;
+    let mut attribute_count = 0;
+
+    loop {
+        let whitespace_len = text[pos..].len() - text[pos..].trim_start().len();
+        pos += whitespace_len;
+        if text[pos..].starts_with('>') {
+            pos += 1;
+            break;
+        }
+        if whitespace_len == 0 {
+            return None;
+        }
+
+        let name_len = text[pos..].find(['=', '>', ' ', '\t', '\r', '\n'])?;
+        let name = &text[pos..pos + name_len];
+        pos += name_len;
+        attribute_count += 1;
+
+        if let Some(rest) = text[pos..].strip_prefix('=') {
+            let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            let value_start = pos + 2;
+            let value_len = text[value_start..].find(quote)?;
+            if name.eq_ignore_ascii_case("href") {
+                href = Some((value_start, &text[value_start..value_start + value_len]));
+            }
+            pos = value_start + value_len + 1;
+        }
+    }
+
+    let (href_pos, href) = href?;
+    let open_tag_len = pos;
+
+    let (text, len) = match text[pos..].find("</a>") {
+        Some(text_len) => {
+            let link_text = &text[pos..pos + text_len];
+            let is_usable =
+                !link_text.trim().is_empty() && !link_text.contains(['[', ']', '\r', '\n']);
+            (
+                Some(link_text).filter(|_| is_usable),
+                pos + text_len + "</a>".len(),
+            )
+        }
+        None => (None, open_tag_len),
+    };
+
+    Some(Anchor {
+        href,
+        href_pos,
+        has_only_href: attribute_count == 1,
+        text,
+        open_tag_len,
+        len,
+    })
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+// Converts already checked URL of package documentation
+// to a path relative to crate index, e.g.
+// `https://docs.rs/foo/0.1.0/foo/a/struct.Foo.html#method.new`
+// to `a/struct.Foo.html#method.new`.
+fn relative_path(docs_url: &DocsUrl) -> String {
+    // skip crate name
+    let segments: Vec<&str> = docs_url
+        .crate_path()
+        .iter()
+        .skip(1)
+        .filter(|segment| !segment.is_empty())
+        .map(String::as_str)
+        .collect();
+
+    let path = match segments.last() {
+        None => "index.html".to_owned(),
+        Some(last) if last.contains('.') => segments.join("/"),
+        Some(_) => format!("{}/index.html", segments.join("/")),
+    };
+
+    match &docs_url.fragment {
+        Some(fragment) => format!("{}#{}", path, fragment),
+        None => path,
+    }
+}
+
+// ======================================================================
+// DATA
+
+// CommonMark HTML block tags of type 6.
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "base",
+    "basefont",
+    "blockquote",
+    "body",
+    "caption",
+    "center",
+    "col",
+    "colgroup",
+    "dd",
+    "details",
+    "dialog",
+    "dir",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "frame",
+    "frameset",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hr",
+    "html",
+    "iframe",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "menu",
+    "menuitem",
+    "nav",
+    "noframes",
+    "ol",
+    "optgroup",
+    "option",
+    "p",
+    "param",
+    "search",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "track",
+    "ul",
+];
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_urls() -> Vec<(String, DocsLayout)> {
+        vec![("https://docs.rs".to_owned(), DocsLayout::DocsRs)]
+    }
+
+    fn relative(url: &str) -> String {
+        relative_path(&DocsUrl::parse(url, "foo", None, &base_urls()).unwrap())
+    }
+
+    fn convert(line: &str, is_in_html_block: bool) -> String {
+        convert_html_links(line, "foo", None, &base_urls(), is_in_html_block, |url| {
+            Ok(url.replace("https://docs.rs/foo/*/foo/struct.A.html", "crate::A"))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn html_block_starts() {
+        assert!(is_html_block_start("<p align=\"center\">\n"));
+        assert!(is_html_block_start("</TD>\n"));
+        assert!(is_html_block_start("<img src=\"x\">\n"));
+        assert!(!is_html_block_start("<a href=\"x\">a</a> b\n"));
+        assert!(!is_html_block_start("    <p>\n"));
+        assert!(!is_html_block_start("a <p>\n"));
+    }
+
+    #[test]
+    fn html_comment_starts() {
+        assert!(is_html_comment_start("<!--\n"));
+        assert!(is_html_comment_start("   <!-- a -->\n"));
+        assert!(!is_html_comment_start("    <!--\n"));
+        assert!(!is_html_comment_start("a <!--\n"));
+    }
+
+    #[test]
+    fn html_comments() {
+        let remove = remove_html_comments;
+        assert_eq!(
+            remove("a <!-- [b] --> c\n", false),
+            ("a   c\n".to_owned(), false)
+        );
+        assert_eq!(remove("a <!-- [b]\n", false), ("a ".to_owned(), true));
+        assert_eq!(remove("[b]\n", true), ("".to_owned(), true));
+        assert_eq!(remove("[b] --> c\n", true), ("  c\n".to_owned(), false));
+        assert_eq!(
+            remove("`<!--` [b] `-->`\n", false),
+            ("`<!--` [b] `-->`\n".to_owned(), false)
+        );
+        assert_eq!(remove("a < b\n", false), ("a < b\n".to_owned(), false));
+    }
+
+    #[test]
+    fn markdown_link() {
+        assert_eq!(
+            convert(
+                "a <a href=\"https://docs.rs/foo/*/foo/struct.A.html\">`A`</a> b",
+                false
+            ),
+            "a [`A`](crate::A) b"
+        );
+    }
+
+    #[test]
+    fn relative_link_in_html_block() {
+        assert_eq!(
+            convert(
+                "<a href='https://docs.rs/foo/*/foo/struct.A.html'>A</a>",
+                true
+            ),
+            "<a href='struct.A.html'>A</a>"
+        );
+    }
+
+    #[test]
+    fn relative_link_with_other_attributes() {
+        assert_eq!(
+            convert(
+                "<a title=\"x\" href=\"https://docs.rs/foo/*/foo/struct.A.html\">A</a>",
+                false
+            ),
+            "<a title=\"x\" href=\"struct.A.html\">A</a>"
+        );
+    }
+
+    #[test]
+    fn ignore_other_links() {
+        let line = "<a href=\"https://example.com\">x</a> <abbr> `<a href=\"https://docs.rs/foo/*/foo/struct.A.html\">`";
+        assert_eq!(convert(line, false), line);
+    }
+
+    #[test]
+    fn relative_paths() {
+        assert_eq!(relative("https://docs.rs/foo"), "index.html");
+        assert_eq!(relative("https://docs.rs/foo/*/foo/#x"), "index.html#x");
+        assert_eq!(relative("https://docs.rs/foo/*/foo/a/b"), "a/b/index.html");
+        assert_eq!(
+            relative("https://docs.rs/foo/*/foo/a/fn.b.html#x"),
+            "a/fn.b.html#x"
+        );
+    }
+}
@@ -0,0 +1,341 @@
+// Intra-doc links like [`Foo::new`] and `[foo](crate::foo)`,
+// which rustdoc resolves without link definitions,
+// but which don't work on GitHub or crates.io.
+
+use super::{docs_urls, ItemKind};
+
+// ======================================================================
+// FUNCTIONS - PUBLIC
+
+// Returns `true` if label of undefined link reference looks like intra-doc link,
+// e.g. `` `Foo::new` ``, `Foo::new`, `fn@foo` or `foo!`.
+pub fn is_intra_doc_label(label: &str) -> bool {
+    match label.strip_prefix('`').and_then(|l| l.strip_suffix('`')) {
+        Some(path) => parse(path).is_some(),
+        None => parse(label).is_some_and(|path| {
+            path.segments.len() > 1 || path.disambiguator.is_some() || !path.suffix.is_empty()
+        }),
+    }
+}
+
+// Returns `true` if URL is intra-doc path starting with `crate::`,
+// e.g. `crate::Foo::new` or `struct@crate::Foo`.
+pub fn is_crate_path(url: &str) -> bool {
+    parse(url).is_some_and(|path| path.segments[0] == "crate")
+}
+
+// Returns base URL of crate documentation in `docs.rs`, ending with `/`,
+// using version `latest` if not given.
+pub fn docs_rs_base_url(
+    package_name: &str,
+    version: Option<&str>,
+    crate_name: Option<&str>,
+) -> String {
+    format!(
+        "https://docs.rs/{}/{}/{}/",
+        package_name,
+        version.unwrap_or("latest"),
+        crate_name.map_or_else(|| package_name.replace('-', "_"), str::to_owned)
+    )
+}
+
+// Returns URL of item with given intra-doc path and kind under given base URL,
+// or `None` if path is invalid or too short for the kind.
+//
+// Path must start with `crate::` or `self::`, or be a bare item path
+// like `Foo` or `Foo::new` of a method, since other roots like `std::`
+// aren't items of this crate. Path can have a disambiguator and a suffix, which are ignored.
+pub fn docs_url(path: &str, kind: ItemKind, base_url: &str) -> Option<String> {
+    let path = parse(path)?;
+    let segments = match path.segments.split_first() {
+        Some((&"crate" | &"self", rest)) => rest,
+        _ => {
+            let is_associated = docs_urls::item_page_prefixes(kind)
+                .is_some_and(|(_, fragment_start)| fragment_start.is_some());
+            if path.segments.len() > 1 + usize::from(is_associated) {
+                return None;
+            }
+            &path.segments[..]
+        }
+    };
+    item_url(segments, kind, base_url.to_owned())
+}
+
+// Returns suggested URL for intra-doc link, and whether it is URL of the item
+// instead of a search URL.
+//
+// Item kind is known only from disambiguator like `enum@Foo` or suffix like `foo!`.
+// Items of unknown or unsupported kinds like constants link to search.
+//
+// Items of `std`, `core` and `alloc` link to `doc.rust-lang.org`
+// and other items to `docs.rs`, using version `latest` if not given.
+pub fn suggested_url(
+    link: &str,
+    package_name: &str,
+    version: Option<&str>,
+    crate_name: Option<&str>,
+) -> Option<(String, bool)> {
+    let link = link
+        .strip_prefix('`')
+        .and_then(|l| l.strip_suffix('`'))
+        .unwrap_or(link);
+    let path = parse(link)?;
+
+    let mut segments = path.segments.as_slice();
+    while let Some((first, rest)) = segments.split_first() {
+        if matches!(*first, "crate" | "self" | "super") {
+            segments = rest;
+        } else {
+            break;
+        }
+    }
+    let base_url = match segments.first() {
+        Some(&first) if matches!(first, "std" | "core" | "alloc") && segments.len() > 1 => {
+            segments = &segments[1..];
+            format!("https://doc.rust-lang.org/{}/", first)
+        }
+        _ => docs_rs_base_url(package_name, version, crate_name),
+    };
+
+    let parent = match segments.split_last() {
+        Some((_, modules)) => modules.last().copied(),
+        None => return Some((base_url, true)),
+    };
+    match item_kind(&path, parent).and_then(|kind| item_url(segments, kind, base_url.clone())) {
+        Some(url) => Some((url, true)),
+        None => Some((
+            format!("{}?search={}", base_url, segments.join("::")),
+            false,
+        )),
+    }
+}
+
+// Returns `true` if text is an identifier like `foo`, `Foo` or `_foo`.
+pub fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+        && text != "_"
+}
+
+// ======================================================================
+// PATH - PRIVATE
+
+// Intra-doc path like `fn@crate::a::foo()`.
+struct Path<'a> {
+    // e.g. `fn` of `fn@foo`
+    disambiguator: Option<&'a str>,
+    // e.g. `["crate", "a", "foo"]`
+    segments: Vec<&'a str>,
+    // one of `()`, `!`, `!()`, `![]`, `!{}` or empty
+    suffix: &'a str,
+}
+
+// Parses intra-doc path, returning `None` if text isn't one.
+fn parse(text: &str) -> Option<Path<'_>> {
+    let (disambiguator, rest) = match text.split_once('@') {
+        Some((disambiguator, rest)) => (Some(disambiguator), rest),
+        None => (None, text),
+    };
+    if disambiguator.is_some_and(|d| d.is_empty() || !d.bytes().all(|b| b.is_ascii_lowercase())) {
+        return None;
+    }
+
+    let suffix_pos = ["!()", "![]", "!{}", "()", "!"]
+        .iter()
+        .find(|suffix| rest.ends_with(*suffix))
+        .map_or(rest.len(), |suffix| rest.len() - suffix.len());
+    let (rest, suffix) = rest.split_at(suffix_pos);
+
+    let segments: Vec<&str> = rest.split("::").collect();
+    if segments.iter().all(|segment| is_identifier(segment)) {
+        Some(Path {
+            disambiguator,
+            segments,
+            suffix,
+        })
+    } else {
+        None
+    }
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+// Returns kind of item given by disambiguator or suffix of its path,
+// or `None` if kind isn't known or is unsupported.
+//
+// Functions with a parent like `Foo::new()` are methods of a struct, enum or trait,
+// so their kind isn't known.
+fn item_kind(path: &Path, parent: Option<&str>) -> Option<ItemKind> {
+    let is_method = parent.is_some_and(starts_with_uppercase);
+    let kind = match (path.disambiguator, path.suffix) {
+        (Some("struct"), _) => ItemKind::Struct,
+        (Some("enum"), _) => ItemKind::Enum,
+        (Some("trait"), _) => ItemKind::Trait,
+        (Some("mod" | "module"), _) => ItemKind::Module,
+        (Some("macro"), _) => ItemKind::Macro,
+        (_, suffix) if suffix.starts_with('!') => ItemKind::Macro,
+        (Some("fn" | "function"), _) | (None, "()") if !is_method => ItemKind::Function,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+// Returns URL of item with given path segments, without `crate`, and kind.
+fn item_url(segments: &[&str], kind: ItemKind, mut url: String) -> Option<String> {
+    let (prefix, fragment_start) = match docs_urls::item_page_prefixes(kind) {
+        Some(prefixes) => prefixes,
+        None => {
+            for segment in segments {
+                url.push_str(segment);
+                url.push('/');
+            }
+            return Some(url);
+        }
+    };
+
+    let (segments, fragment) = match fragment_start {
+        Some(fragment_start) => {
+            let (name, segments) = segments.split_last()?;
+            (segments, Some(format!("#{}.{}", fragment_start, name)))
+        }
+        None => (segments, None),
+    };
+    let (name, modules) = segments.split_last()?;
+    for module in modules {
+        url.push_str(module);
+        url.push('/');
+    }
+    url.push_str(&format!("{}.{}.html", prefix, name));
+    url.extend(fragment);
+    Some(url)
+}
+
+fn starts_with_uppercase(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggest(link: &str) -> String {
+        suggested_url(link, "foo-bar", Some("0.1.0"), None)
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn intra_doc_labels() {
+        for label in [
+            "`Foo`",
+            "`Foo::new`",
+            "Foo::new",
+            "`fn@foo`",
+            "foo!",
+            "foo()",
+        ] {
+            assert!(is_intra_doc_label(label), "{}", label);
+        }
+        for label in ["foo", "Foo", "`a b`", "1", "x", "`Vec<T>`", "a::", "@foo"] {
+            assert!(!is_intra_doc_label(label), "{}", label);
+        }
+    }
+
+    #[test]
+    fn crate_paths() {
+        assert!(is_crate_path("crate::Foo"));
+        assert!(is_crate_path("struct@crate::Foo"));
+        assert!(is_crate_path("crate"));
+        assert!(!is_crate_path("crates::Foo"));
+        assert!(!is_crate_path("https://docs.rs/crate"));
+    }
+
+    #[test]
+    fn docs_urls() {
+        let url = |path, kind| docs_url(path, kind, "https://docs.rs/foo/0.1.0/foo/");
+        assert_eq!(
+            url("crate::a::Foo::new", ItemKind::StructMethod).as_deref(),
+            Some("https://docs.rs/foo/0.1.0/foo/a/struct.Foo.html#method.new")
+        );
+        assert_eq!(
+            url("self::A::B", ItemKind::EnumVariant).as_deref(),
+            Some("https://docs.rs/foo/0.1.0/foo/enum.A.html#variant.B")
+        );
+        assert_eq!(
+            url("T::f", ItemKind::TraitMethod).as_deref(),
+            Some("https://docs.rs/foo/0.1.0/foo/trait.T.html#tymethod.f")
+        );
+        assert_eq!(
+            url("macro@crate::a::foo!", ItemKind::Macro).as_deref(),
+            Some("https://docs.rs/foo/0.1.0/foo/a/macro.foo.html")
+        );
+        assert_eq!(
+            url("crate", ItemKind::Module).as_deref(),
+            Some("https://docs.rs/foo/0.1.0/foo/")
+        );
+        assert_eq!(url("crate", ItemKind::Struct), None);
+        assert_eq!(url("Foo", ItemKind::StructMethod), None);
+        assert_eq!(url("a b", ItemKind::Struct), None);
+        assert_eq!(url("std::vec::Vec", ItemKind::Struct), None);
+        assert_eq!(url("super::Foo", ItemKind::Struct), None);
+        assert_eq!(url("a::b", ItemKind::Module), None);
+        assert_eq!(url("Foo::new::x", ItemKind::StructMethod), None);
+        assert_eq!(
+            url("Foo", ItemKind::Struct).as_deref(),
+            Some("https://docs.rs/foo/0.1.0/foo/struct.Foo.html")
+        );
+    }
+
+    #[test]
+    fn suggested_urls() {
+        let base = "https://docs.rs/foo-bar/0.1.0/foo_bar/";
+        assert_eq!(suggest("crate"), base);
+        assert_eq!(suggest("a::foo()"), format!("{}a/fn.foo.html", base));
+        assert_eq!(suggest("fn@foo"), format!("{}fn.foo.html", base));
+        assert_eq!(suggest("foo!"), format!("{}macro.foo.html", base));
+        assert_eq!(suggest("mod@a::b"), format!("{}a/b/", base));
+        assert_eq!(suggest("enum@self::A"), format!("{}enum.A.html", base));
+        assert_eq!(
+            suggest("struct@std::vec::Vec"),
+            "https://doc.rust-lang.org/std/vec/struct.Vec.html"
+        );
+        assert_eq!(
+            suggested_url("struct@Foo", "foo", None, Some("bar")),
+            Some((
+                "https://docs.rs/foo/latest/bar/struct.Foo.html".to_owned(),
+                true
+            ))
+        );
+    }
+
+    #[test]
+    fn suggested_search_urls() {
+        let base = "https://docs.rs/foo-bar/0.1.0/foo_bar/";
+        assert_eq!(suggest("`Foo`"), format!("{}?search=Foo", base));
+        assert_eq!(suggest("`foo`"), format!("{}?search=foo", base));
+        assert_eq!(
+            suggest("crate::a::Foo::new"),
+            format!("{}?search=a::Foo::new", base)
+        );
+        assert_eq!(suggest("Foo::new()"), format!("{}?search=Foo::new", base));
+        assert_eq!(suggest("fn@Foo::new"), format!("{}?search=Foo::new", base));
+        assert_eq!(suggest("MAX"), format!("{}?search=MAX", base));
+        assert_eq!(suggest("const@a::B"), format!("{}?search=a::B", base));
+        assert_eq!(
+            suggest("std::vec::Vec"),
+            "https://doc.rust-lang.org/std/?search=vec::Vec"
+        );
+        assert_eq!(
+            suggested_url("Foo", "foo", None, Some("bar")),
+            Some((
+                "https://docs.rs/foo/latest/bar/?search=Foo".to_owned(),
+                false
+            ))
+        );
+    }
+}
@@ -0,0 +1,4241 @@
+//! Library source of [`readme-rustdocifier`](https://docs.rs/readme-rustdocifier),
+//! shared with its procedural macros in `readme-rustdocifier-macros`.
+//!
+//! Use this through `readme-rustdocifier`, which re-exports the public API.
+
+#![deny(missing_docs)]
+#![forbid(unsafe_code)]
+
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anchors::Anchors;
+use docs_urls::{DocsUrl, DocsUrlKind};
+use link_definitions::{Link, LinkDefinition};
+
+mod alerts;
+mod anchors;
+mod code_blocks;
+mod dependencies;
+mod docs_urls;
+mod embed;
+mod emoji;
+mod features;
+mod html_links;
+mod intra_doc_links;
+mod link_definitions;
+mod manifest;
+mod semver;
+mod toml;
+
+// ======================================================================
+// ERROR - PUBLIC
+
+/// Error returned by [`rustdocify`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Check with severity [`Severity::Deny`] found a problem.
+    ///
+    /// See [`ReadmeRustdocifier::severity`].
+    DeniedWarning(Warning),
+
+    /// Embedding a file failed, e.g. because file or region doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```markdown
+    /// <!-- rustdocify:embed examples/no_such_file.rs -->
+    /// ```
+    EmbedFailed(String),
+
+    /// Checking embeds is enabled with [`ReadmeRustdocifier::check_embeds`]
+    /// and readme copy of embedded file or region differs from the file.
+    EmbedOutOfDate(String),
+
+    /// Path given to [`docs_rs_url`] isn't a valid intra-doc path of an item of this crate,
+    /// e.g. `std::vec::Vec`, or it is too short for the item kind, e.g. method path without type.
+    InvalidIntraDocPath(String),
+
+    /// Manifest given to [`ReadmeRustdocifier::manifest`] is invalid or unsupported.
+    ///
+    /// # Example
+    ///
+    /// ```toml
+    /// [features]
+    /// foo = "bar"
+    /// ```
+    InvalidManifest(String),
+
+    /// Reading or writing a file failed, e.g. because readme or `Cargo.toml` doesn't exist.
+    IoFailed(String),
+
+    /// Environment variable needed by [`ReadmeRustdocifier::from_env`]
+    /// or [`ReadmeRustdocifier::build`] is not set,
+    /// e.g. because they were not called from a build script.
+    MissingEnvVar(String),
+
+    /// Readme has features marker, or checking dependencies is enabled with
+    /// [`ReadmeRustdocifier::check_dependencies`], but no manifest was given.
+    ///
+    /// # Example
+    ///
+    /// ```markdown
+    /// <!-- rustdocify:features -->
+    /// ```
+    MissingManifest(String),
+
+    /// Version was given to [`rustdocify`] but URL is missing a version.
+    ///
+    /// # Example
+    ///
+    /// ```markdown
+    /// [foo]: https://docs.rs/foo
+    /// ```
+    MissingVersionInUrl(String),
+
+    /// Readme has top-level header that is not first header.
+    ///
+    /// # Example
+    ///
+    /// <!-- Note: Using extra `#`:s here because rustdoc removes one. -->
+    /// ```markdown
+    /// ### First
+    /// ## Second
+    /// ```
+    NonFirstTopLevelHeader(String),
+
+    /// URL is not recognized as valid.
+    ///
+    /// This means that either URL is invalid or this crate has a bug.
+    ///
+    /// # Example
+    ///
+    /// ```markdown
+    /// [foo]: https://docs.rs/foo/*/foo/hello_world.html
+    /// ```
+    UnrecognizedUrl(String),
+
+    /// Crate name was given to [`rustdocify`] but URL has different crate name.
+    ///
+    /// # Example
+    ///
+    /// ```markdown
+    /// [foo]: https://docs.rs/foo/*/DIFFERENT_CRATE
+    /// ```
+    WrongCrateNameInUrl(String),
+
+    /// Version was given to [`rustdocify`] but URL has different version.
+    ///
+    /// # Example
+    ///
+    /// ```markdown
+    /// [foo]: https://docs.rs/foo/DIFFERENT_VERSION
+    /// ```
+    WrongVersionInUrl(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::DeniedWarning(warning) => {
+                write!(f, "denied warning: {}", warning)
+            }
+
+            Error::EmbedFailed(message) => {
+                write!(f, "embed failed: {}", message)
+            }
+
+            Error::EmbedOutOfDate(name) => {
+                write!(f, "embed out of date: {}", name)
+            }
+
+            Error::InvalidIntraDocPath(path) => {
+                write!(f, "invalid intra-doc path: {}", path)
+            }
+
+            Error::InvalidManifest(message) => {
+                write!(f, "invalid manifest: {}", message)
+            }
+
+            Error::IoFailed(message) => {
+                write!(f, "io failed: {}", message)
+            }
+
+            Error::MissingEnvVar(name) => {
+                write!(f, "missing environment variable: {}", name)
+            }
+
+            Error::MissingManifest(line) => {
+                write!(f, "missing manifest for: {}", line)
+            }
+
+            Error::MissingVersionInUrl(url) => {
+                write!(f, "missing version in url: {}", url)
+            }
+
+            Error::NonFirstTopLevelHeader(header) => {
+                write!(f, "non-first top level header: {}", header)
+            }
+
+            Error::UnrecognizedUrl(url) => {
+                write!(f, "unrecognized url: {}", url)
+            }
+
+            Error::WrongCrateNameInUrl(url) => {
+                write!(f, "wrong crate name in url: {}", url)
+            }
+
+            Error::WrongVersionInUrl(url) => {
+                write!(f, "wrong version in url: {}", url)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// ======================================================================
+// WARNING - PUBLIC
+
+/// Warning returned by [`ReadmeRustdocifier::check`]
+/// and [`ReadmeRustdocifier::rustdocify_with_warnings`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    /// Line number in readme, starting from 1.
+    pub line: usize,
+    /// Kind of warning.
+    pub kind: WarningKind,
+}
+
+/// Kind of [`Warning`].
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// Code block has conflicting attributes.
+    ///
+    /// # Example
+    ///
+    /// ````markdown
+    /// ```ignore,should_panic
+    /// ```
+    /// ````
+    ConflictingCodeBlockAttributes(String, String),
+
+    /// Link reference definition has same label as an earlier definition,
+    /// so it is ignored.
+    ///
+    /// Labels are matched case-insensitively.
+    ///
+    /// # Example
+    ///
+    /// ```markdown
+    /// [foo]: https://example.com/a
+    /// [Foo]: https://example.com/b
+    /// ```
+    DuplicateLinkDefinition(String),
+
+    /// Link works only in rustdoc, as it is an intra-doc link
+    /// without link definition or its URL is a `crate::` path,
+    /// so e.g. GitHub shows it as text with brackets or as a broken link.
+    ///
+    /// Contains the link and suggested URL. If item kind isn't given
+    /// with a disambiguator like `enum@Foo` or a suffix like `foo()` or `foo!`,
+    /// suggested URL is a search URL like `https://docs.rs/foo/latest/foo/?search=Foo`.
+    ///
+    /// # Example
+    ///
+    /// ```markdown
+    /// See [`Foo::new`] and [bar](crate::bar).
+    /// ```
+    RustdocOnlyLink(String, String),
+
+    /// Code block has unknown attribute, which may be a typo.
+    ///
+    /// # Example
+    ///
+    /// ````markdown
+    /// ```rust,no-run
+    /// ```
+    /// ````
+    UnknownCodeBlockAttribute(String),
+
+    /// URL of an item has a fragment which isn't recognized,
+    /// so it is kept as-is like in `crate::Foo#fragment`.
+    ///
+    /// # Example
+    ///
+    /// ```markdown
+    /// [foo]: https://docs.rs/foo/*/foo/struct.Foo.html#impl-Display-for-Foo
+    /// ```
+    UnknownFragment(String),
+
+    /// Link has a fragment which isn't id of any header or HTML element in readme,
+    /// so it is kept as-is.
+    ///
+    /// # Example
+    ///
+    /// ```markdown
+    /// [foo](#no-such-header)
+    /// ```
+    UnknownHeaderLink(String),
+
+    /// Checking dependencies is enabled with [`ReadmeRustdocifier::check_dependencies`]
+    /// and Rust code block uses a crate which is neither a dependency
+    /// nor a dev-dependency of the package.
+    ///
+    /// # Example
+    ///
+    /// ````markdown
+    /// ```rust
+    /// use tokio::runtime::Runtime;
+    /// ```
+    /// ````
+    UndeclaredDependency(String),
+
+    /// Link reference has no link reference definition,
+    /// so e.g. GitHub shows it as text with brackets.
+    ///
+    /// Shortcut references like `[label]` are reported only if label is a code span,
+    /// since text in brackets like `[WIP]` is usually not meant to be a link.
+    ///
+    /// # Example
+    ///
+    /// ```markdown
+    /// See [foo][bar].
+    /// ```
+    UndefinedLinkReference(String),
+
+    /// Code block has no info string so it is compiled as a doctest,
+    /// but its content doesn't look like Rust.
+    ///
+    /// # Example
+    ///
+    /// ````markdown
+    /// ```
+    /// $ cargo run
+    /// ```
+    /// ````
+    UntaggedNonRustCodeBlock,
+
+    /// Link reference definition is not used by any link.
+    ///
+    /// # Example
+    ///
+    /// ```markdown
+    /// [foo]: https://example.com
+    /// ```
+    UnusedLinkDefinition(String),
+
+    /// `<!-- rustdocify:prelude ... -->` marker is not followed by a Rust code block,
+    /// so it is discarded at next code block or marker of any language.
+    ///
+    /// # Example
+    ///
+    /// ````markdown
+    /// <!-- rustdocify:prelude
+    /// use foo::Bar;
+    /// -->
+    /// ```toml
+    /// ```
+    /// ````
+    UnusedPrelude,
+}
+
+impl WarningKind {
+    /// Returns the check which produces this kind of warning.
+    pub fn check(&self) -> Check {
+        match self {
+            WarningKind::ConflictingCodeBlockAttributes(..) => {
+                Check::ConflictingCodeBlockAttributes
+            }
+            WarningKind::DuplicateLinkDefinition(_) => Check::DuplicateLinkDefinition,
+            WarningKind::RustdocOnlyLink(..) => Check::RustdocOnlyLink,
+            WarningKind::UnknownCodeBlockAttribute(_) => Check::UnknownCodeBlockAttribute,
+            WarningKind::UnknownFragment(_) => Check::UnknownFragment,
+            WarningKind::UnknownHeaderLink(_) => Check::UnknownHeaderLink,
+            WarningKind::UndeclaredDependency(_) => Check::UndeclaredDependency,
+            WarningKind::UndefinedLinkReference(_) => Check::UndefinedLinkReference,
+            WarningKind::UntaggedNonRustCodeBlock => Check::UntaggedNonRustCodeBlock,
+            WarningKind::UnusedLinkDefinition(_) => Check::UnusedLinkDefinition,
+            WarningKind::UnusedPrelude => Check::UnusedPrelude,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarningKind::ConflictingCodeBlockAttributes(a, b) => {
+                write!(f, "conflicting code block attributes: {} and {}", a, b)
+            }
+
+            WarningKind::DuplicateLinkDefinition(label) => {
+                write!(f, "duplicate link definition: {}", label)
+            }
+
+            WarningKind::RustdocOnlyLink(link, url) => {
+                write!(f, "link works only in rustdoc: {}, use e.g. {}", link, url)
+            }
+
+            WarningKind::UnknownCodeBlockAttribute(attribute) => {
+                write!(f, "unknown code block attribute: {}", attribute)
+            }
+
+            WarningKind::UnknownFragment(url) => {
+                write!(f, "unknown fragment in url: {}", url)
+            }
+
+            WarningKind::UnknownHeaderLink(link) => {
+                write!(f, "unknown header link: {}", link)
+            }
+
+            WarningKind::UndeclaredDependency(name) => {
+                write!(f, "undeclared dependency: {}", name)
+            }
+
+            WarningKind::UndefinedLinkReference(label) => {
+                write!(f, "undefined link reference: {}", label)
+            }
+
+            WarningKind::UntaggedNonRustCodeBlock => {
+                write!(f, "code block without info string doesn't look like Rust")
+            }
+
+            WarningKind::UnusedLinkDefinition(label) => {
+                write!(f, "unused link definition: {}", label)
+            }
+
+            WarningKind::UnusedPrelude => {
+                write!(f, "prelude is not followed by a Rust code block")
+            }
+        }
+    }
+}
+
+// ======================================================================
+// CHECK - PUBLIC
+
+/// Check which produces warnings, for [`ReadmeRustdocifier::severity`].
+///
+/// Each check produces the [`WarningKind`] of same name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Check {
+    /// See [`WarningKind::ConflictingCodeBlockAttributes`].
+    ConflictingCodeBlockAttributes,
+    /// See [`WarningKind::DuplicateLinkDefinition`].
+    DuplicateLinkDefinition,
+    /// See [`WarningKind::RustdocOnlyLink`].
+    RustdocOnlyLink,
+    /// See [`WarningKind::UndeclaredDependency`].
+    UndeclaredDependency,
+    /// See [`WarningKind::UndefinedLinkReference`].
+    UndefinedLinkReference,
+    /// See [`WarningKind::UnknownCodeBlockAttribute`].
+    UnknownCodeBlockAttribute,
+    /// See [`WarningKind::UnknownFragment`].
+    UnknownFragment,
+    /// See [`WarningKind::UnknownHeaderLink`].
+    UnknownHeaderLink,
+    /// See [`WarningKind::UntaggedNonRustCodeBlock`].
+    UntaggedNonRustCodeBlock,
+    /// See [`WarningKind::UnusedLinkDefinition`].
+    UnusedLinkDefinition,
+    /// See [`WarningKind::UnusedPrelude`].
+    UnusedPrelude,
+}
+
+/// Severity of a [`Check`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// Problems are ignored.
+    Allow,
+    /// Problems are returned as warnings. This is the default.
+    Warn,
+    /// Problems are returned as [`Error::DeniedWarning`].
+    Deny,
+}
+
+// ======================================================================
+// VERSIONPOLICY - PUBLIC
+
+/// Which versions in `docs.rs` URLs are accepted when version is given,
+/// for [`ReadmeRustdocifier::version_policy`].
+///
+/// Versions are compared as [semantic versions], so build metadata like `+build.5`
+/// is ignored and pre-release versions like `1.0.0-beta.2` are matched like Cargo does.
+/// Versions which aren't valid semantic versions must be equal.
+///
+/// | URL version        | `Exact` | `ExactOrLatest` | `Compatible` | `CompatibleWithoutWildcards` |
+/// | ------------------ | ------- | --------------- | ------------ | ---------------------------- |
+/// | `0.3.2`            | ✓       | ✓               | ✓            | ✓                            |
+/// | `0.3`, `^0.3`      |         |                 | ✓            | ✓                            |
+/// | `*`, `latest`      |         | ✓               | ✓            |                              |
+/// | no version         |         | ✓               | ✓            |                              |
+/// | `0.2`, `0.3.1`     |         |                 |              |                              |
+///
+/// Table shows accepted URL versions when package version is `0.3.2`.
+///
+/// [semantic versions]: https://semver.org
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VersionPolicy {
+    /// URL version must be the package version. This is the default.
+    #[default]
+    Exact,
+    /// URL version must be the package version, `*` or `latest`, or URL has no version.
+    ExactOrLatest,
+    /// URL version must be a version requirement like `0.3` or `~0.3.1`
+    /// which the package version matches, `*` or `latest`, or URL has no version.
+    /// Full versions like `0.3.1` must be the package version.
+    ///
+    /// `*`, `latest` and missing version are accepted only for versions
+    /// which aren't pre-releases, because they don't link to pre-releases.
+    Compatible,
+    /// URL version must be a version requirement like `0.3` or `~0.3.1`
+    /// which the package version matches, but not `*` or `latest`.
+    /// Full versions like `0.3.1` must be the package version.
+    CompatibleWithoutWildcards,
+}
+
+// ======================================================================
+// DOCSLAYOUT - PUBLIC
+
+/// Layout of paths under a documentation base URL,
+/// for [`ReadmeRustdocifier::docs_base_url`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocsLayout {
+    /// `BASE/PACKAGE/VERSION/CRATE/...`, like `https://docs.rs`.
+    DocsRs,
+    /// `BASE/VERSION/CRATE/...`, e.g. documentation of one package on a private server.
+    Versioned,
+    /// `BASE/CRATE/...`, like output of `cargo doc`.
+    ///
+    /// URLs have no version, so versions are not checked.
+    /// Base URL can have documentation of several crates,
+    /// so only URLs with crate name of this package are converted.
+    CargoDoc,
+}
+
+impl DocsLayout {
+    fn has_version(self) -> bool {
+        self != DocsLayout::CargoDoc
+    }
+}
+
+// ======================================================================
+// ITEMKIND - PUBLIC
+
+/// Kind of item, for [`docs_rs_url`].
+///
+/// These are the kinds of items whose `docs.rs` links are converted
+/// to intra-doc links.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemKind {
+    /// Enum, e.g. `crate::Foo` for `enum.Foo.html`.
+    Enum,
+    /// Method of enum, e.g. `crate::Foo::new` for `enum.Foo.html#method.new`.
+    EnumMethod,
+    /// Variant of enum, e.g. `crate::Foo::Bar` for `enum.Foo.html#variant.Bar`.
+    EnumVariant,
+    /// Function, e.g. `crate::foo` for `fn.foo.html`.
+    Function,
+    /// Macro, e.g. `crate::foo` for `macro.foo.html`.
+    Macro,
+    /// Module, e.g. `crate::foo` for `foo/index.html`, or `crate` for crate root.
+    Module,
+    /// Struct, e.g. `crate::Foo` for `struct.Foo.html`.
+    Struct,
+    /// Method of struct, e.g. `crate::Foo::new` for `struct.Foo.html#method.new`.
+    StructMethod,
+    /// Trait, e.g. `crate::Foo` for `trait.Foo.html`.
+    Trait,
+    /// Required method of trait, e.g. `crate::Foo::foo` for `trait.Foo.html#tymethod.foo`.
+    TraitMethod,
+}
+
+// ======================================================================
+// DOCSRSURL - PUBLIC
+
+/// `docs.rs` URL of a module or item page, split into its components.
+///
+/// This is how [`rustdocify`] understands `docs.rs` URLs,
+/// e.g. `https://docs.rs/foo/0.1.0/foo/a/struct.Foo.html#method.new` has
+/// package `foo`, version `0.1.0`, crate `foo`, modules `a`, item kind [`ItemKind::Struct`],
+/// item name `Foo` and fragment `method.new`.
+///
+/// - Parsing fails with [`Error::UnrecognizedUrl`] for URLs which aren't
+///   converted to intra-doc links, e.g. crate pages, source code and searches,
+///   and for URLs with crate, module or item names which aren't identifiers.
+/// - Path segments and fragment are percent-decoded,
+///   and [`Display`](fmt::Display) encodes them again when needed.
+/// - Display uses `https://docs.rs` and adds version `latest` if URL has crate but no version.
+///
+/// ```
+/// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+/// use readme_rustdocifier::{DocsRsUrl, ItemKind};
+///
+/// let url: DocsRsUrl = "https://docs.rs/foo/0.1.0/foo/a/struct.Foo.html#method.new"
+///     .parse()
+///     .unwrap();
+///
+/// assert_eq!(url.package, "foo");
+/// assert_eq!(url.version.as_deref(), Some("0.1.0"));
+/// assert_eq!(url.modules, ["a"]);
+/// assert_eq!(url.kind, ItemKind::Struct);
+/// assert_eq!(url.name.as_deref(), Some("Foo"));
+/// assert_eq!(url.intra_doc_path(), "crate::a::Foo::new");
+/// assert_eq!(
+///     url.to_string(),
+///     "https://docs.rs/foo/0.1.0/foo/a/struct.Foo.html#method.new"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocsRsUrl {
+    /// Package name, e.g. `foo-bar`.
+    pub package: String,
+    /// Version, e.g. `0.1.0`, `0.1` or `latest`, or `None` if URL has no version.
+    pub version: Option<String>,
+    /// Crate name, e.g. `foo_bar`, or `None` if URL has no crate name.
+    pub crate_name: Option<String>,
+    /// Path of modules within the crate, including the module of a module page.
+    pub modules: Vec<String>,
+    /// Kind of item of an item page, or [`ItemKind::Module`] for a module page.
+    ///
+    /// Methods and variants are not pages but fragments of item pages,
+    /// so this is never e.g. [`ItemKind::StructMethod`].
+    pub kind: ItemKind,
+    /// Name of item of an item page, or `None` for a module page.
+    pub name: Option<String>,
+    /// Fragment without `#`, e.g. `method.new`.
+    pub fragment: Option<String>,
+}
+
+impl DocsRsUrl {
+    /// Returns intra-doc path of the page, with fragment if any,
+    /// as converted by [`rustdocify`].
+    ///
+    /// Fragments of methods and variants become part of the path,
+    /// e.g. `crate::Foo::new` for `struct.Foo.html#method.new`.
+    /// Other fragments are percent-encoded, e.g. `crate::Foo#a%20b`.
+    pub fn intra_doc_path(&self) -> String {
+        docs_urls::intra_doc_path(
+            &self.modules,
+            self.name.as_deref().map(|name| (self.kind, name)),
+            self.fragment.as_deref(),
+            false,
+        )
+    }
+}
+
+impl FromStr for DocsRsUrl {
+    type Err = Error;
+
+    fn from_str(url: &str) -> Result<Self, Error> {
+        let unrecognized = || Error::UnrecognizedUrl(url.to_owned());
+
+        let package = DEFAULT_DOCS_BASE_URLS
+            .iter()
+            .find_map(|base_url| url.strip_prefix(base_url)?.strip_prefix('/'))
+            .and_then(|rest| rest.split(['/', '#', '?']).next())
+            .filter(|package| {
+                !package.is_empty()
+                    && *package != "crate"
+                    && package
+                        .bytes()
+                        .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+            })
+            .ok_or_else(unrecognized)?;
+        let base_urls: Vec<(String, DocsLayout)> = DEFAULT_DOCS_BASE_URLS
+            .iter()
+            .map(|base_url| (base_url.to_string(), DocsLayout::DocsRs))
+            .collect();
+        let docs_url = DocsUrl::parse(url, package, None, &base_urls)
+            .filter(|docs_url| docs_url.kind == DocsUrlKind::Rustdoc && docs_url.query.is_none())
+            .ok_or_else(unrecognized)?;
+
+        let mut docs_rs_url = DocsRsUrl {
+            package: package.to_owned(),
+            version: docs_url.version().map(str::to_owned),
+            crate_name: None,
+            modules: Vec::new(),
+            kind: ItemKind::Module,
+            name: None,
+            fragment: docs_url.fragment.clone(),
+        };
+
+        // same cases as in `convert_url`
+        let path = docs_url.crate_path();
+        if path.is_empty() || path == ["index.html"] {
+            return Ok(docs_rs_url);
+        }
+        docs_rs_url.crate_name = Some(path[0].clone());
+        let last = path.last().unwrap().as_str();
+        if path.len() == 1 || !last.contains('.') {
+            docs_rs_url.modules = path[1..].to_vec();
+        } else {
+            docs_rs_url.modules = path[1..path.len() - 1].to_vec();
+            if last != "index.html" {
+                let (kind, name) = docs_urls::item_page(last).ok_or_else(unrecognized)?;
+                docs_rs_url.kind = kind;
+                docs_rs_url.name = Some(name.to_owned());
+            }
+        }
+
+        // e.g. `struct.A%3CT%3E.html` isn't an item page which rustdoc generates
+        let names = docs_rs_url
+            .crate_name
+            .iter()
+            .chain(&docs_rs_url.modules)
+            .chain(&docs_rs_url.name);
+        for name in names {
+            if !intra_doc_links::is_identifier(name) {
+                return Err(unrecognized());
+            }
+        }
+        Ok(docs_rs_url)
+    }
+}
+
+impl fmt::Display for DocsRsUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let encode = |segment: &str| docs_urls::percent_encode(segment, false);
+
+        write!(f, "https://docs.rs/{}", self.package)?;
+        if let Some(crate_name) = &self.crate_name {
+            let version = self.version.as_deref().unwrap_or("latest");
+            write!(f, "/{}/{}/", encode(version), encode(crate_name))?;
+            for module in &self.modules {
+                write!(f, "{}/", encode(module))?;
+            }
+            if let Some(name) = &self.name {
+                // item kinds of item pages always have a prefix
+                let (prefix, _) = docs_urls::item_page_prefixes(self.kind).unwrap_or(("", None));
+                write!(f, "{}.{}.html", prefix, encode(name))?;
+            }
+        } else if let Some(version) = &self.version {
+            write!(f, "/{}", encode(version))?;
+        }
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{}", docs_urls::percent_encode(fragment, true))?;
+        }
+        Ok(())
+    }
+}
+
+// ======================================================================
+// READMERUSTDOCIFIER - PUBLIC
+
+/// Rustdocifier with optional features.
+///
+/// Without any optional features this works like [`rustdocify`].
+///
+/// # Example
+///
+/// ```
+/// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+/// use readme_rustdocifier::ReadmeRustdocifier;
+///
+/// let readme = "# foo\n\n## Features\n\n<!-- rustdocify:features -->\n";
+/// let manifest = "[features]\n# Enables `std` support.\nstd = []\n";
+///
+/// let rustdocified = ReadmeRustdocifier::new("foo")
+///     .version("0.1.0")
+///     .crate_name("foo")
+///     .manifest(manifest)
+///     .rustdocify(readme)
+///     .unwrap();
+///
+/// assert!(rustdocified.contains("| `std` |  | Enables `std` support. |"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ReadmeRustdocifier {
+    package_name: String,
+    version: Option<String>,
+    version_policy: VersionPolicy,
+    crate_name: Option<String>,
+    docs_base_urls: Vec<(String, DocsLayout)>,
+    source_base_url: Option<String>,
+    disambiguators: bool,
+    manifest: Option<String>,
+    emoji_shortcodes: bool,
+    code_block_infos: Vec<(String, String)>,
+    base_dir: Option<PathBuf>,
+    readme_path: PathBuf,
+    manifest_path: Option<PathBuf>,
+    check_embeds: bool,
+    check_dependencies: bool,
+    severities: Vec<(Check, Severity)>,
+}
+
+impl ReadmeRustdocifier {
+    /// Creates new rustdocifier for given package.
+    pub fn new(package_name: &str) -> Self {
+        Self {
+            package_name: package_name.to_owned(),
+            version: None,
+            version_policy: VersionPolicy::Exact,
+            crate_name: None,
+            docs_base_urls: DEFAULT_DOCS_BASE_URLS
+                .iter()
+                .map(|base_url| (base_url.to_string(), DocsLayout::DocsRs))
+                .collect(),
+            source_base_url: None,
+            disambiguators: false,
+            manifest: None,
+            emoji_shortcodes: false,
+            code_block_infos: Vec::new(),
+            base_dir: None,
+            readme_path: PathBuf::from("README.md"),
+            manifest_path: None,
+            check_embeds: false,
+            check_dependencies: false,
+            severities: Vec::new(),
+        }
+    }
+
+    /// Creates new rustdocifier for package with given `Cargo.toml`.
+    ///
+    /// - Package name and version are read from `package.name` and `package.version`.
+    /// - Crate name is read from `lib.name`, or is package name
+    ///   with `-` replaced by `_`, which is the default name of library target.
+    /// - [`ReadmeRustdocifier::readme_path`] is read from `package.readme`,
+    ///   or is `README.md` by default.
+    /// - [`ReadmeRustdocifier::manifest`] is set to contents of `Cargo.toml`.
+    /// - [`ReadmeRustdocifier::base_dir`] is set to directory containing `Cargo.toml`.
+    ///
+    /// Fields like `version.workspace = true` are read from `[workspace.package]`
+    /// of the workspace root, which is found from `package.workspace`
+    /// or by searching parent directories.
+    ///
+    /// ```no_run
+    /// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+    /// use readme_rustdocifier::ReadmeRustdocifier;
+    ///
+    /// let rustdocified = ReadmeRustdocifier::from_manifest_path("foo/Cargo.toml")?
+    ///     .rustdocify_readme()?;
+    /// # Ok::<(), readme_rustdocifier::Error>(())
+    /// ```
+    pub fn from_manifest_path(manifest_path: impl AsRef<Path>) -> Result<Self, Error> {
+        let manifest_path = manifest_path.as_ref();
+        let package = manifest::Package::read(manifest_path)?;
+
+        let mut rustdocifier = Self::new(&package.name)
+            .crate_name(&package.crate_name)
+            .manifest(&package.manifest)
+            .base_dir(manifest_path.parent().unwrap_or(Path::new("")));
+        rustdocifier.version = package.version;
+        rustdocifier.manifest_path = Some(manifest_path.to_owned());
+        if let Some(readme) = package.readme {
+            rustdocifier.readme_path = readme;
+        }
+        Ok(rustdocifier)
+    }
+
+    /// Creates new rustdocifier for the package being built by Cargo,
+    /// for use in build scripts and procedural macros.
+    ///
+    /// This is same as [`ReadmeRustdocifier::from_manifest_path`] with
+    /// `Cargo.toml` in `CARGO_MANIFEST_DIR`, except that
+    ///
+    /// - Package name and version are read from `CARGO_PKG_NAME` and `CARGO_PKG_VERSION`.
+    /// - Crate name is read from `CARGO_CRATE_NAME` if set.
+    ///   Cargo doesn't set it for build scripts.
+    ///
+    /// See [`ReadmeRustdocifier::build`] for an example.
+    pub fn from_env() -> Result<Self, Error> {
+        let manifest_dir = PathBuf::from(env_var("CARGO_MANIFEST_DIR")?);
+        let mut rustdocifier = Self::from_manifest_path(manifest_dir.join("Cargo.toml"))?;
+
+        rustdocifier.package_name = env_var("CARGO_PKG_NAME")?;
+        rustdocifier.version = Some(env_var("CARGO_PKG_VERSION")?);
+        if let Ok(crate_name) = env::var("CARGO_CRATE_NAME") {
+            if crate_name != "build_script_build" {
+                rustdocifier.crate_name = Some(crate_name);
+            }
+        }
+        Ok(rustdocifier)
+    }
+
+    /// Checks that links have this version,
+    /// or a version accepted by [`ReadmeRustdocifier::version_policy`].
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_owned());
+        self
+    }
+
+    /// Sets which versions in links are accepted when version is given.
+    ///
+    /// Default is [`VersionPolicy::Exact`].
+    ///
+    /// ```
+    /// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+    /// use readme_rustdocifier::{ReadmeRustdocifier, VersionPolicy};
+    ///
+    /// let rustdocifier = ReadmeRustdocifier::new("foo").version("0.3.2");
+    /// let readme = "[`Foo`]: https://docs.rs/foo/0.3/foo/struct.Foo.html\n";
+    ///
+    /// assert!(rustdocifier.rustdocify(readme).is_err());
+    /// assert_eq!(
+    ///     rustdocifier
+    ///         .version_policy(VersionPolicy::Compatible)
+    ///         .rustdocify(readme),
+    ///     Ok("[`Foo`]: crate::Foo\n".to_owned())
+    /// );
+    /// ```
+    pub fn version_policy(mut self, policy: VersionPolicy) -> Self {
+        self.version_policy = policy;
+        self
+    }
+
+    /// Checks that links have this exact crate name, if any.
+    pub fn crate_name(mut self, crate_name: &str) -> Self {
+        self.crate_name = Some(crate_name.to_owned());
+        self
+    }
+
+    /// Adds base URL of documentation of the package.
+    ///
+    /// Links under base URLs are checked and converted like `docs.rs` links.
+    /// By default base URLs are `https://docs.rs`, `http://docs.rs`,
+    /// `https://www.docs.rs` and `http://www.docs.rs`, with layout [`DocsLayout::DocsRs`].
+    ///
+    /// Can be given multiple times.
+    ///
+    /// ```
+    /// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+    /// use readme_rustdocifier::{DocsLayout, ReadmeRustdocifier};
+    ///
+    /// let rustdocifier = ReadmeRustdocifier::new("foo")
+    ///     .docs_base_url("https://docs.example.com/foo/", DocsLayout::Versioned);
+    ///
+    /// assert_eq!(
+    ///     rustdocifier.rustdocify("[A]: https://docs.example.com/foo/0.1.0/foo/struct.A.html"),
+    ///     Ok("[A]: crate::A".to_owned())
+    /// );
+    /// ```
+    pub fn docs_base_url(mut self, base_url: &str, layout: DocsLayout) -> Self {
+        self.docs_base_urls
+            .push((base_url.trim_end_matches('/').to_owned(), layout));
+        self
+    }
+
+    /// Converts links to source code in documentation, like
+    /// `https://docs.rs/PACKAGE/VERSION/src/CRATE/a/b.rs.html#10-20`,
+    /// to links like `SOURCE_BASE_URL/a/b.rs#L10-L20`, e.g. to source in repository.
+    ///
+    /// Source paths in documentation are relative to the directory of crate root,
+    /// so base URL is usually like `https://github.com/OWNER/REPO/blob/TAG/src`.
+    /// By default links to source code are checked but not converted.
+    ///
+    /// ```
+    /// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+    /// use readme_rustdocifier::ReadmeRustdocifier;
+    ///
+    /// let rustdocifier = ReadmeRustdocifier::new("foo")
+    ///     .source_base_url("https://github.com/owner/foo/blob/v0.1.0/src/");
+    ///
+    /// assert_eq!(
+    ///     rustdocifier.rustdocify("[a]: https://docs.rs/foo/0.1.0/src/foo/a.rs.html#10-20"),
+    ///     Ok("[a]: https://github.com/owner/foo/blob/v0.1.0/src/a.rs#L10-L20".to_owned())
+    /// );
+    /// ```
+    pub fn source_base_url(mut self, base_url: &str) -> Self {
+        self.source_base_url = Some(base_url.trim_end_matches('/').to_owned());
+        self
+    }
+
+    /// Sets whether converted links have intra-doc disambiguators,
+    /// like `fn@crate::foo`, `macro@crate::foo`, `struct@crate::Foo` and `mod@crate::foo`.
+    ///
+    /// This avoids ambiguous links, which rustdoc warns about,
+    /// e.g. when crate has both a function and a macro named `foo`.
+    /// Methods get `method@` and enum variants `variant@`.
+    /// Default is `false`.
+    ///
+    /// ```
+    /// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+    /// use readme_rustdocifier::ReadmeRustdocifier;
+    ///
+    /// let rustdocifier = ReadmeRustdocifier::new("foo").disambiguators(true);
+    ///
+    /// assert_eq!(
+    ///     rustdocifier.rustdocify("[a]: https://docs.rs/foo/*/foo/macro.a.html"),
+    ///     Ok("[a]: macro@crate::a".to_owned())
+    /// );
+    /// ```
+    pub fn disambiguators(mut self, disambiguators: bool) -> Self {
+        self.disambiguators = disambiguators;
+        self
+    }
+
+    /// Sets contents of `Cargo.toml` of the package.
+    ///
+    /// This is needed for the `<!-- rustdocify:features -->` marker,
+    /// which is replaced with a table of features in `[features]`.
+    /// Comment lines directly above a feature are used as its description.
+    pub fn manifest(mut self, manifest: &str) -> Self {
+        self.manifest = Some(manifest.to_owned());
+        self
+    }
+
+    /// Replaces known GitHub emoji shortcodes like `:rocket:` with emojis.
+    ///
+    /// Shortcodes within code spans, code blocks, link destinations, autolinks
+    /// and HTML tags are not replaced.
+    /// Default is `false`.
+    pub fn emoji_shortcodes(mut self, expand: bool) -> Self {
+        self.emoji_shortcodes = expand;
+        self
+    }
+
+    /// Replaces language of code blocks having given `language` with `replacement`.
+    ///
+    /// Language is the first word of code block info string,
+    /// or `""` for code blocks without info string.
+    /// Info string of replaced code blocks is also cleaned up
+    /// by removing words like `title="..."` which rustdoc doesn't understand.
+    /// Attributes of `replacement` which are already on the code block or conflict with its
+    /// attributes, e.g. `no_run` with `ignore`, are not added.
+    ///
+    /// Can be given multiple times.
+    ///
+    /// ```
+    /// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+    /// use readme_rustdocifier::ReadmeRustdocifier;
+    ///
+    /// let rustdocifier = ReadmeRustdocifier::new("foo")
+    ///     .code_block_info("rust", "rust,no_run")
+    ///     .code_block_info("", "text")
+    ///     .code_block_info("console", "text");
+    ///
+    /// assert_eq!(
+    ///     rustdocifier.rustdocify("```rust title=\"main.rs\"\n```\n```\n```"),
+    ///     Ok("```rust,no_run\n```\n```text\n```".to_owned())
+    /// );
+    /// ```
+    pub fn code_block_info(mut self, language: &str, replacement: &str) -> Self {
+        self.code_block_infos
+            .push((language.to_owned(), replacement.to_owned()));
+        self
+    }
+
+    /// Sets directory which paths of embedded files are relative to.
+    ///
+    /// Default is current directory,
+    /// which in build scripts is the directory containing `Cargo.toml`.
+    pub fn base_dir(mut self, base_dir: impl AsRef<Path>) -> Self {
+        self.base_dir = Some(base_dir.as_ref().to_owned());
+        self
+    }
+
+    /// Sets path of readme read by [`ReadmeRustdocifier::rustdocify_readme`]
+    /// and [`ReadmeRustdocifier::build`], relative to [`ReadmeRustdocifier::base_dir`].
+    ///
+    /// Default is `README.md`.
+    pub fn readme_path(mut self, readme_path: impl AsRef<Path>) -> Self {
+        self.readme_path = readme_path.as_ref().to_owned();
+        self
+    }
+
+    /// Checks that readme copies of embedded files are up to date.
+    ///
+    /// If enabled, code block directly after `<!-- rustdocify:embed ... -->`
+    /// must match the embedded file or region,
+    /// or else [`Error::EmbedOutOfDate`] is returned.
+    /// Default is `false`.
+    pub fn check_embeds(mut self, check: bool) -> Self {
+        self.check_embeds = check;
+        self
+    }
+
+    /// Checks that Rust code blocks only use crates which are dependencies
+    /// or dev-dependencies in the manifest given to [`ReadmeRustdocifier::manifest`].
+    ///
+    /// Used crates are found from `use` declarations, `extern crate` items
+    /// and roots of paths like `tokio::spawn`.
+    /// Undeclared crates are returned as warnings by [`ReadmeRustdocifier::check`].
+    /// Default is `false`.
+    pub fn check_dependencies(mut self, check: bool) -> Self {
+        self.check_dependencies = check;
+        self
+    }
+
+    /// Sets severity of given check. Default is [`Severity::Warn`] for all checks.
+    ///
+    /// ```
+    /// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+    /// use readme_rustdocifier::{Check, Error, ReadmeRustdocifier, Severity};
+    ///
+    /// let readme = "```\n$ cargo run\n```\n";
+    /// let rustdocifier = ReadmeRustdocifier::new("foo");
+    ///
+    /// assert!(matches!(
+    ///     rustdocifier
+    ///         .clone()
+    ///         .severity(Check::UntaggedNonRustCodeBlock, Severity::Deny)
+    ///         .rustdocify(readme),
+    ///     Err(Error::DeniedWarning(_))
+    /// ));
+    /// assert_eq!(
+    ///     rustdocifier
+    ///         .severity(Check::UntaggedNonRustCodeBlock, Severity::Allow)
+    ///         .check(readme),
+    ///     Ok(vec![])
+    /// );
+    /// ```
+    pub fn severity(mut self, check: Check, severity: Severity) -> Self {
+        self.severities.retain(|(c, _)| *c != check);
+        self.severities.push((check, severity));
+        self
+    }
+
+    /// Rustdocifies readme of the package being built by Cargo,
+    /// for use in build scripts.
+    ///
+    /// - Reads readme from [`ReadmeRustdocifier::readme_path`].
+    /// - Writes result to `README-rustdocified.md` in `OUT_DIR`,
+    ///   unless the file already has the same content.
+    ///   This avoids needless recompilation of the crate.
+    /// - Prints `cargo:rerun-if-changed` for readme, `Cargo.toml`
+    ///   and all embedded files.
+    /// - Prints warnings like `cargo:warning=README.md:3: ...`,
+    ///   which Cargo shows without failing the build.
+    ///
+    /// # Example
+    ///
+    /// `build.rs`:
+    ///
+    /// ```no_run
+    /// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+    /// fn main() -> Result<(), readme_rustdocifier::Error> {
+    ///     readme_rustdocifier::ReadmeRustdocifier::from_env()?.build()
+    /// }
+    /// ```
+    ///
+    /// `lib.rs`:
+    ///
+    /// ```ignore
+    /// #![doc = include_str!(concat!(env!("OUT_DIR"), "/README-rustdocified.md"))]
+    /// ```
+    pub fn build(&self) -> Result<(), Error> {
+        let output_path = PathBuf::from(env_var("OUT_DIR")?).join("README-rustdocified.md");
+        let conversion = self.convert_readme()?;
+
+        for path in &conversion.files {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+        let readme_name = self.readme_path.file_name().unwrap_or_default();
+        for warning in &conversion.warnings {
+            println!(
+                "cargo:warning={}:{}: {}",
+                Path::new(readme_name).display(),
+                warning.line,
+                warning.kind
+            );
+        }
+        write_if_changed(&output_path, &conversion.result)
+    }
+
+    /// Reads and rustdocifies readme from [`ReadmeRustdocifier::readme_path`].
+    pub fn rustdocify_readme(&self) -> Result<String, Error> {
+        self.convert_readme().map(|conversion| conversion.result)
+    }
+
+    // Reads and rustdocifies readme from `readme_path`,
+    // returning also full paths of files read, for `include_readme!` to track.
+    #[doc(hidden)]
+    pub fn rustdocify_readme_with_files(&self) -> Result<(String, Vec<PathBuf>), Error> {
+        self.convert_readme()
+            .map(|conversion| (conversion.result, conversion.files))
+    }
+
+    // Reads and converts readme from `readme_path`.
+    //
+    // Files read during conversion include also readme and `Cargo.toml` if known.
+    fn convert_readme(&self) -> Result<Conversion, Error> {
+        let (readme_path, readme) = self.read_readme()?;
+        let mut conversion = self.convert(&readme)?;
+        let files = [readme_path].into_iter().chain(self.manifest_path.clone());
+        conversion.files.splice(0..0, files);
+        Ok(conversion)
+    }
+
+    // Reads readme from `readme_path`, returning its full path and contents.
+    fn read_readme(&self) -> Result<(PathBuf, String), Error> {
+        let readme_path = self
+            .base_dir
+            .as_deref()
+            .unwrap_or(Path::new(""))
+            .join(&self.readme_path);
+        let readme = fs::read_to_string(&readme_path)
+            .map_err(|error| Error::IoFailed(format!("{}: {}", readme_path.display(), error)))?;
+        Ok((readme_path, readme))
+    }
+
+    /// Rustdocifies the given readme.
+    ///
+    /// See [`rustdocify`] for details.
+    pub fn rustdocify(&self, readme: &str) -> Result<String, Error> {
+        self.convert(readme).map(|conversion| conversion.result)
+    }
+
+    /// Rustdocifies the given readme, returning also found warnings.
+    ///
+    /// See [`ReadmeRustdocifier::check`] for the checks.
+    pub fn rustdocify_with_warnings(&self, readme: &str) -> Result<(String, Vec<Warning>), Error> {
+        self.convert(readme)
+            .map(|conversion| (conversion.result, conversion.warnings))
+    }
+
+    /// Checks the given readme, returning found warnings.
+    ///
+    /// Errors are returned as with [`ReadmeRustdocifier::rustdocify`].
+    ///
+    /// Following checks are done:
+    /// - Code blocks without info string, which will be compiled as doctests,
+    ///   but don't look like Rust.
+    /// - Code block info strings with unknown attributes,
+    ///   e.g. typos like `no-run` or `ignored`.
+    /// - Code block info strings with conflicting attributes like `ignore,should_panic`.
+    /// - Crates used by Rust code blocks which aren't dependencies,
+    ///   if enabled with [`ReadmeRustdocifier::check_dependencies`].
+    /// - Links to items with unknown fragment like `#impl-Display-for-Foo`.
+    /// - Link references without definition, unused link reference definitions
+    ///   and definitions with duplicate labels.
+    /// - Links which work only in rustdoc, i.e. intra-doc links like `` [`Foo::new`] ``
+    ///   without link definition and links to `crate::` paths.
+    ///
+    /// Severity of each check can be changed with [`ReadmeRustdocifier::severity`].
+    ///
+    /// ```
+    /// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+    /// use readme_rustdocifier::{ReadmeRustdocifier, Warning, WarningKind};
+    ///
+    /// let readme = "# foo\n\n```no-run\nfoo();\n```\n";
+    ///
+    /// assert_eq!(
+    ///     ReadmeRustdocifier::new("foo").check(readme),
+    ///     Ok(vec![Warning {
+    ///         line: 3,
+    ///         kind: WarningKind::UnknownCodeBlockAttribute("no-run".to_owned()),
+    ///     }])
+    /// );
+    /// ```
+    pub fn check(&self, readme: &str) -> Result<Vec<Warning>, Error> {
+        self.convert(readme).map(|conversion| conversion.warnings)
+    }
+
+    /// Appends missing link definitions to the given readme,
+    /// for references which look like intra-doc links, e.g. `` [`Foo::new`] ``.
+    ///
+    /// This makes links which rustdoc resolves also work on GitHub and crates.io.
+    /// Definitions have the URL suggested by [`WarningKind::RustdocOnlyLink`].
+    /// They are added only for links with known item kind,
+    /// i.e. with a disambiguator like `enum@Foo` or a suffix like `foo()` or `foo!`,
+    /// since other links would get a search URL.
+    ///
+    /// Errors are returned as with [`ReadmeRustdocifier::rustdocify`].
+    ///
+    /// ```
+    /// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+    /// use readme_rustdocifier::ReadmeRustdocifier;
+    ///
+    /// let readme = "# foo\n\nSee [`Foo`] and [`foo()`].\n";
+    ///
+    /// assert_eq!(
+    ///     ReadmeRustdocifier::new("foo")
+    ///         .version("0.1.0")
+    ///         .add_missing_link_definitions(readme),
+    ///     Ok(concat!(
+    ///         "# foo\n\nSee [`Foo`] and [`foo()`].\n\n",
+    ///         "[`foo()`]: https://docs.rs/foo/0.1.0/foo/fn.foo.html\n",
+    ///     )
+    ///     .to_owned())
+    /// );
+    /// ```
+    pub fn add_missing_link_definitions(&self, readme: &str) -> Result<String, Error> {
+        let definitions = self
+            .clone()
+            .severity(Check::RustdocOnlyLink, Severity::Warn)
+            .convert(readme)?
+            .missing_link_definitions;
+
+        let mut result = readme.to_owned();
+        if definitions.is_empty() {
+            return Ok(result);
+        }
+        if !result.is_empty() && !result.ends_with('\n') {
+            result.push('\n');
+        }
+        let last_line = result.lines().last().unwrap_or("");
+        let last_line_is_definition = LinkDefinition::parse(&[last_line], 3).is_some();
+        if !result.is_empty() && !last_line_is_definition && !last_line.trim().is_empty() {
+            result.push('\n');
+        }
+        for (label, url) in definitions {
+            result.push_str(&format!("[{}]: {}\n", label, url));
+        }
+        Ok(result)
+    }
+
+    fn severity_of(&self, check: Check) -> Severity {
+        self.severities
+            .iter()
+            .find(|(c, _)| *c == check)
+            .map_or(Severity::Warn, |(_, severity)| *severity)
+    }
+
+    fn convert(&self, readme: &str) -> Result<Conversion, Error> {
+        let lines: Vec<&str> = readme.split_inclusive('\n').collect();
+        let mut result = String::with_capacity(readme.len());
+        let mut converter = Converter::new(self, &lines);
+        if self.check_dependencies {
+            let manifest = self.manifest.as_deref().ok_or_else(|| {
+                Error::MissingManifest("ReadmeRustdocifier::check_dependencies".to_owned())
+            })?;
+            let mut crates = dependencies::declared_crates(manifest)?;
+            crates.push(self.package_name.replace('-', "_"));
+            crates.extend(self.crate_name.clone());
+            converter.declared_crates = Some(crates);
+        }
+        converter.convert(&lines, 1, &mut result)?;
+        converter.discard_prelude()?;
+        converter.check_link_references()?;
+        Ok(Conversion {
+            result,
+            warnings: converter.warnings,
+            files: converter.embedded_files,
+            missing_link_definitions: converter.missing_link_definitions,
+        })
+    }
+}
+
+// ======================================================================
+// FUNCTIONS - PUBLIC
+
+/// Rustdocifies the given readme.
+///
+/// - Removes top-level header.
+/// - Changes other headers to be one level higher.
+/// - Converts `docs.rs` links of given `package_name` to rustdoc format.
+/// - Doesn't change anything within code blocks.
+/// - If `version` is given, checks that links have this exact version.
+/// - If `crate_name` is given, checks that links have this exact crate name, if any.
+///
+/// See [crate index] for an example and more details.
+/// See [`ReadmeRustdocifier`] for optional features.
+///
+/// [crate index]: crate
+pub fn rustdocify(
+    readme: &str,
+    package_name: &str,
+    version: Option<&str>,
+    crate_name: Option<&str>,
+) -> Result<String, Error> {
+    let mut rustdocifier = ReadmeRustdocifier::new(package_name);
+    if let Some(version) = version {
+        rustdocifier = rustdocifier.version(version);
+    }
+    if let Some(crate_name) = crate_name {
+        rustdocifier = rustdocifier.crate_name(crate_name);
+    }
+    rustdocifier.rustdocify(readme)
+}
+
+/// Returns `docs.rs` URL of item with given intra-doc path and kind.
+///
+/// This is the inverse of link conversion done by [`rustdocify`],
+/// e.g. for adding link definitions for intra-doc links to readme.
+///
+/// - Path must start with `crate::` or `self::`, or be a bare item path
+///   like `Foo` or `Foo::new`. Intra-doc disambiguators like `fn@`
+///   and suffixes like `()` and `!` are ignored.
+/// - If `version` is not given, `latest` is used.
+/// - If `crate_name` is not given, `package_name` with `-` replaced by `_` is used.
+///
+/// See also [`ReadmeRustdocifier::add_missing_link_definitions`].
+///
+/// ```
+/// # extern crate readme_rustdocifier_core as readme_rustdocifier;
+/// use readme_rustdocifier::{docs_rs_url, ItemKind};
+///
+/// assert_eq!(
+///     docs_rs_url("crate::a::Foo::new", ItemKind::StructMethod, "foo", Some("0.1.0"), None),
+///     Ok("https://docs.rs/foo/0.1.0/foo/a/struct.Foo.html#method.new".to_owned())
+/// );
+/// ```
+pub fn docs_rs_url(
+    path: &str,
+    kind: ItemKind,
+    package_name: &str,
+    version: Option<&str>,
+    crate_name: Option<&str>,
+) -> Result<String, Error> {
+    let base_url = intra_doc_links::docs_rs_base_url(package_name, version, crate_name);
+    intra_doc_links::docs_url(path, kind, &base_url)
+        .ok_or_else(|| Error::InvalidIntraDocPath(path.to_owned()))
+}
+
+// ======================================================================
+// CONVERSION - PRIVATE
+
+// Result of converting readme.
+struct Conversion {
+    result: String,
+    warnings: Vec<Warning>,
+    // files read during conversion
+    files: Vec<PathBuf>,
+    // labels and suggested URLs of intra-doc links without link definition
+    missing_link_definitions: Vec<(String, String)>,
+}
+
+// ======================================================================
+// CONVERTER - PRIVATE
+
+struct Converter<'a> {
+    rustdocifier: &'a ReadmeRustdocifier,
+    anchors: Anchors,
+    is_first_header: bool,
+    // attributes for next code block from `<!-- rustdocify:attributes ... -->`
+    code_block_attributes: Vec<String>,
+    // line number of `<!-- rustdocify:prelude ... -->` and hidden lines
+    // for next code block, which must be a Rust code block
+    code_block_prelude: Option<(usize, Vec<String>)>,
+    // crates which code blocks can use, if dependencies are checked
+    declared_crates: Option<Vec<String>>,
+    warnings: Vec<Warning>,
+    embedded_files: Vec<PathBuf>,
+    // labels of link reference definitions and references, with line numbers
+    // and whether reference is a shortcut reference like `[label]`
+    link_definitions: Vec<(String, usize)>,
+    link_references: Vec<(String, usize, bool)>,
+    missing_link_definitions: Vec<(String, String)>,
+}
+
+impl<'a> Converter<'a> {
+    fn new(rustdocifier: &'a ReadmeRustdocifier, lines: &[&str]) -> Self {
+        Self {
+            rustdocifier,
+            anchors: Anchors::collect(lines, rustdocifier.emoji_shortcodes),
+            is_first_header: true,
+            code_block_attributes: Vec::new(),
+            code_block_prelude: None,
+            declared_crates: None,
+            warnings: Vec::new(),
+            embedded_files: Vec::new(),
+            link_definitions: Vec::new(),
+            link_references: Vec::new(),
+            missing_link_definitions: Vec::new(),
+        }
+    }
+
+    // Converts given lines, appending them to `result`.
+    //
+    // `first_line_number` is 1-based line number of first line, used for warnings.
+    //
+    // This is called recursively for contents of alerts and embedded files.
+    fn convert(
+        &mut self,
+        lines: &[&str],
+        first_line_number: usize,
+        result: &mut String,
+    ) -> Result<(), Error> {
+        let mut code_block_level = None;
+        let mut is_rust_code_block = false;
+        let mut is_in_html_block = false;
+        // whether previous line ends within HTML comment `<!-- ... -->`
+        let mut is_in_html_comment = false;
+        // whether previous line is paragraph text, which link definitions can't interrupt
+        let mut is_in_paragraph = false;
+        let mut previous_quote_depth = 0;
+        // content column of current list item, or 0
+        let mut list_indent = 0;
+        let mut n = 0;
+
+        while n < lines.len() {
+            let line = lines[n];
+            n += 1;
+            let line_number = first_line_number + n - 1;
+
+            if let Some(level) = code_block_level {
+                // IN CODE BLOCK
+
+                if is_code_block_end(line, level) {
+                    code_block_level = None;
+                    result.push_str(line);
+                } else if let Some(line) =
+                    code_blocks::hidden_line(line).filter(|_| is_rust_code_block)
+                {
+                    result.push_str(&line);
+                } else {
+                    result.push_str(line);
+                }
+            } else {
+                // NOT IN CODE BLOCK
+
+                if line.trim().is_empty() {
+                    is_in_html_block = false;
+                } else if html_links::is_html_block_start(line) {
+                    is_in_html_block = true;
+                }
+                // lines of HTML comment are handled like lines of HTML block
+                let is_in_html = is_in_html_block
+                    || is_in_html_comment
+                    || html_links::is_html_comment_start(line);
+                is_in_html_comment = html_links::remove_html_comments(line, is_in_html_comment).1;
+
+                let quote_depth = link_definitions::quote_depth(line);
+                let list_item_column = link_definitions::list_item_content_column(line);
+                let starts_block = list_item_column.is_some() || quote_depth > previous_quote_depth;
+                previous_quote_depth = quote_depth;
+                if let Some(column) = list_item_column {
+                    list_indent = column;
+                } else if !line.trim().is_empty()
+                    && !is_in_paragraph
+                    && line.len() - line.trim_start().len() < list_indent
+                {
+                    list_indent = 0;
+                }
+                let can_start_definition = !is_in_html && (!is_in_paragraph || starts_block);
+                is_in_paragraph = false;
+
+                code_block_level = is_code_block_start(line);
+
+                if let Some(level) = code_block_level {
+                    let attributes = std::mem::take(&mut self.code_block_attributes);
+                    let line = code_blocks::rewrite_info(
+                        line,
+                        level,
+                        &self.rustdocifier.code_block_infos,
+                        &attributes,
+                    )
+                    .unwrap_or_else(|| line.to_owned());
+                    result.push_str(&line);
+
+                    let info = line[level..].trim();
+                    let content: Vec<&str> = lines[n..]
+                        .iter()
+                        .take_while(|line| !is_code_block_end(line, level))
+                        .copied()
+                        .collect();
+                    for kind in code_blocks::lint(info, &content) {
+                        self.warn(line_number, kind)?;
+                    }
+
+                    is_rust_code_block = code_blocks::is_rust(info);
+                    let undeclared = match &self.declared_crates {
+                        Some(declared) if is_rust_code_block => {
+                            dependencies::undeclared_crates(&content, declared)
+                        }
+                        _ => Vec::new(),
+                    };
+                    for (index, name) in undeclared {
+                        self.warn(
+                            line_number + 1 + index,
+                            WarningKind::UndeclaredDependency(name),
+                        )?;
+                    }
+                    if !is_rust_code_block {
+                        self.discard_prelude()?;
+                    } else if let Some((_, prelude)) = self.code_block_prelude.take() {
+                        let newline = match line_ending(&line) {
+                            "" => "\n",
+                            newline => newline,
+                        };
+                        for prelude_line in prelude {
+                            if prelude_line.is_empty() {
+                                result.push('#');
+                            } else {
+                                result.push_str("# ");
+                                result.push_str(&prelude_line);
+                            }
+                            result.push_str(newline);
+                        }
+                    }
+                } else if let Some(attributes) = code_blocks::attributes_marker(line) {
+                    self.code_block_attributes = attributes;
+                } else if let Some((prelude, line_count)) =
+                    code_blocks::prelude_marker(&lines[n - 1..])
+                {
+                    self.discard_prelude()?;
+                    self.code_block_prelude = Some((line_number, prelude));
+                    n += line_count - 1;
+                    is_in_html_comment = false;
+                } else if let Some(embed) = embed::Embed::from_marker(line) {
+                    n += self.convert_embed(&embed, &lines[n..], first_line_number + n, result)?;
+                } else if let Some(kind) = alerts::alert_kind(line) {
+                    let body = alerts::alert_body(&lines[n..]);
+                    n += body.len();
+
+                    let mut converted_body = String::new();
+                    self.convert(
+                        &body,
+                        first_line_number + n - body.len(),
+                        &mut converted_body,
+                    )?;
+                    result.push_str(&alerts::render_alert(kind, line, &converted_body));
+                } else if features::is_features_marker(line) {
+                    if let Some(manifest) = &self.rustdocifier.manifest {
+                        result.push_str(&features::features_table(manifest)?);
+                        result.push_str(line_ending(line));
+                    } else {
+                        return Err(Error::MissingManifest(line.to_owned()));
+                    }
+                } else if let Some(line) = convert_header_line(line, &mut self.is_first_header)? {
+                    self.push_text(line, line_number, false, result)?;
+                } else if let Some(definition) =
+                    LinkDefinition::parse(&lines[n - 1..], list_indent + 3)
+                        .filter(|_| can_start_definition)
+                {
+                    n += definition.line_count - 1;
+                    self.convert_link_definition(
+                        &definition,
+                        &lines[n - definition.line_count..n],
+                        line_number,
+                        result,
+                    )?;
+                } else {
+                    is_in_paragraph = !line.trim().is_empty() && !is_in_html;
+                    self.push_text(line, line_number, is_in_html, result)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Converts embedded file or region, replacing readme copy in code block
+    // at start of `next_lines`, if any.
+    //
+    // Returns number of lines used from `next_lines`.
+    fn convert_embed(
+        &mut self,
+        embed: &embed::Embed,
+        next_lines: &[&str],
+        next_line_number: usize,
+        result: &mut String,
+    ) -> Result<usize, Error> {
+        let base_dir = self.rustdocifier.base_dir.as_deref();
+        let path = embed.full_path(base_dir);
+        if !self.embedded_files.contains(&path) {
+            self.embedded_files.push(path);
+        }
+        let content = embed.read(base_dir)?;
+
+        let mut info = embed.language();
+        let mut line_count = 0;
+
+        if let Some(level) = next_lines
+            .first()
+            .and_then(|line| is_code_block_start(line))
+        {
+            if let Some(end) = next_lines[1..]
+                .iter()
+                .position(|line| is_code_block_end(line, level))
+            {
+                let copy = next_lines[1..=end].concat();
+                if self.rustdocifier.check_embeds && !embed::is_up_to_date(&copy, &content) {
+                    return Err(Error::EmbedOutOfDate(embed.name()));
+                }
+                info = next_lines[0][level..].trim();
+                line_count = end + 2;
+            }
+        }
+
+        let code_block = embed::code_block(info, &content);
+        let code_block: Vec<&str> = code_block.iter().map(String::as_str).collect();
+        self.convert(&code_block, next_line_number, result)?;
+
+        Ok(line_count)
+    }
+
+    // Appends text line to `result`, converting fragment links and HTML links
+    // and expanding emoji shortcodes if enabled.
+    fn push_text(
+        &mut self,
+        line: &str,
+        line_number: usize,
+        is_in_html_block: bool,
+        result: &mut String,
+    ) -> Result<(), Error> {
+        if !is_in_html_block {
+            let text = html_links::remove_html_comments(line, false).0;
+            for link in link_definitions::links(&text) {
+                match link {
+                    Link::Reference(label) => {
+                        self.link_references
+                            .push((label.to_owned(), line_number, false));
+                    }
+                    Link::Shortcut(label) => {
+                        self.link_references
+                            .push((label.to_owned(), line_number, true));
+                    }
+                    Link::Inline(url) => self.check_crate_path(url, line_number)?,
+                }
+            }
+        }
+        let mut warnings = Vec::new();
+        let line = self.anchors.convert_inline_links(line, &mut warnings);
+        let line = html_links::convert_html_links(
+            &line,
+            &self.rustdocifier.package_name,
+            self.rustdocifier.crate_name.as_deref(),
+            &self.rustdocifier.docs_base_urls,
+            is_in_html_block,
+            |url| self.convert_url(url, &mut warnings),
+        )?;
+        for kind in warnings {
+            self.warn(line_number, kind)?;
+        }
+        if self.rustdocifier.emoji_shortcodes {
+            result.push_str(&emoji::expand_shortcodes(&line));
+        } else {
+            result.push_str(&line);
+        }
+        Ok(())
+    }
+
+    // Converts link definition like `[foo]: https://docs.rs/...`,
+    // which is in given lines, appending them to `result`.
+    fn convert_link_definition(
+        &mut self,
+        definition: &LinkDefinition,
+        lines: &[&str],
+        line_number: usize,
+        result: &mut String,
+    ) -> Result<(), Error> {
+        let label = link_definitions::normalize_label(definition.label);
+        if self
+            .link_definitions
+            .iter()
+            .any(|(defined, _)| link_definitions::normalize_label(defined) == label)
+        {
+            self.warn(
+                line_number,
+                WarningKind::DuplicateLinkDefinition(definition.label.to_owned()),
+            )?;
+        } else {
+            self.link_definitions
+                .push((definition.label.to_owned(), line_number));
+        }
+
+        let url_line = lines[definition.url_line];
+        let url = &url_line[definition.url_range.clone()];
+        self.check_crate_path(url, line_number + definition.url_line)?;
+        let mut warnings = Vec::new();
+        let link = if url.starts_with('#') {
+            self.anchors.convert_fragment(url, &mut warnings)
+        } else {
+            self.convert_url(url, &mut warnings)?
+        };
+        for kind in warnings {
+            self.warn(line_number + definition.url_line, kind)?;
+        }
+
+        for (index, line) in lines.iter().enumerate() {
+            if index == definition.url_line && link != url {
+                // angle brackets are removed when URL is converted
+                let bracket_len = definition.has_angle_brackets as usize;
+                result.push_str(&line[..definition.url_range.start - bracket_len]);
+                result.push_str(&link);
+                result.push_str(&line[definition.url_range.end + bracket_len..]);
+            } else {
+                result.push_str(line);
+            }
+        }
+        Ok(())
+    }
+
+    // Converts URL, adding warning to `warnings` if URL has unknown fragment,
+    // which is kept as-is like in `crate::Foo#fragment`.
+    fn convert_url(&self, url: &str, warnings: &mut Vec<WarningKind>) -> Result<String, Error> {
+        let link = convert_url(url, self.rustdocifier)?;
+        // skip disambiguator like `struct@`
+        let path = link.split_once('@').map_or(link.as_str(), |(_, path)| path);
+        if path.starts_with("crate::") && link.contains('#') && is_item_url(url) {
+            warnings.push(WarningKind::UnknownFragment(url.to_owned()));
+        }
+        Ok(link)
+    }
+
+    // Adds warnings for undefined link references and unused link definitions,
+    // keeping warnings ordered by line number.
+    //
+    // Also collects missing link definitions for intra-doc links.
+    fn check_link_references(&mut self) -> Result<(), Error> {
+        let defined: Vec<String> = self
+            .link_definitions
+            .iter()
+            .map(|(label, _)| link_definitions::normalize_label(label))
+            .collect();
+        let used: Vec<String> = self
+            .link_references
+            .iter()
+            .map(|(label, _, _)| link_definitions::normalize_label(label))
+            .collect();
+
+        let mut warnings = Vec::new();
+        for ((label, line, is_shortcut), normalized) in self.link_references.iter().zip(&used) {
+            if !defined.contains(normalized) {
+                let suggested_url = Some(label)
+                    .filter(|label| intra_doc_links::is_intra_doc_label(label))
+                    .and_then(|label| self.suggested_url(label));
+                let kind = match suggested_url {
+                    Some((url, is_item_url)) => {
+                        // definitions with search URLs aren't added
+                        let is_missing =
+                            self.missing_link_definitions.iter().all(|(missing, _)| {
+                                link_definitions::normalize_label(missing) != *normalized
+                            });
+                        if is_item_url && is_missing {
+                            self.missing_link_definitions
+                                .push((label.clone(), url.clone()));
+                        }
+                        WarningKind::RustdocOnlyLink(label.clone(), url)
+                    }
+                    // shortcut references like `[WIP]` or `[0, 1]` may be just text
+                    // in brackets, so only code spans like [`foo`] are reported
+                    None if *is_shortcut && !is_code_span_label(label) => continue,
+                    None => WarningKind::UndefinedLinkReference(label.clone()),
+                };
+                warnings.push((*line, kind));
+            }
+        }
+        for ((label, line), normalized) in self.link_definitions.iter().zip(&defined) {
+            if !used.contains(normalized) {
+                warnings.push((*line, WarningKind::UnusedLinkDefinition(label.clone())));
+            }
+        }
+        for (line, kind) in warnings {
+            self.warn(line, kind)?;
+        }
+        self.warnings.sort_by_key(|warning| warning.line);
+        Ok(())
+    }
+
+    // Discards pending prelude, if any, adding warning at its marker.
+    fn discard_prelude(&mut self) -> Result<(), Error> {
+        match self.code_block_prelude.take() {
+            Some((line_number, _)) => self.warn(line_number, WarningKind::UnusedPrelude),
+            None => Ok(()),
+        }
+    }
+
+    // Adds warning if URL is a `crate::` path, which works only in rustdoc.
+    fn check_crate_path(&mut self, url: &str, line_number: usize) -> Result<(), Error> {
+        if intra_doc_links::is_crate_path(url) {
+            if let Some((suggested_url, _)) = self.suggested_url(url) {
+                self.warn(
+                    line_number,
+                    WarningKind::RustdocOnlyLink(url.to_owned(), suggested_url),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    // Returns suggested URL for intra-doc link, and whether it is URL of the item
+    // instead of a search URL.
+    fn suggested_url(&self, link: &str) -> Option<(String, bool)> {
+        intra_doc_links::suggested_url(
+            link,
+            &self.rustdocifier.package_name,
+            self.rustdocifier.version.as_deref(),
+            self.rustdocifier.crate_name.as_deref(),
+        )
+    }
+
+    // Adds warning with severity configured with `ReadmeRustdocifier::severity`.
+    fn warn(&mut self, line: usize, kind: WarningKind) -> Result<(), Error> {
+        let warning = Warning { line, kind };
+        match self.rustdocifier.severity_of(warning.kind.check()) {
+            Severity::Allow => Ok(()),
+            Severity::Warn => {
+                self.warnings.push(warning);
+                Ok(())
+            }
+            Severity::Deny => Err(Error::DeniedWarning(warning)),
+        }
+    }
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+// Returns
+// - `Ok(Some(..))` on successful conversion
+// - `Ok(None)` if this is not a header line
+// - `Err(..)` on error
+fn convert_header_line<'a>(
+    line: &'a str,
+    is_first_header: &mut bool,
+) -> Result<Option<&'a str>, Error> {
+    let bytes = line.as_bytes();
+
+    let mut level = 0;
+    while level < line.len() && bytes[level] == b'#' {
+        level += 1;
+    }
+
+    if level > 0 && bytes.get(level) == Some(&b' ') {
+        if level == 1 {
+            if *is_first_header {
+                *is_first_header = false;
+                Ok(Some(""))
+            } else {
+                Err(Error::NonFirstTopLevelHeader(line.to_owned()))
+            }
+        } else {
+            *is_first_header = false;
+            Ok(Some(&line[1..]))
+        }
+    } else {
+        Ok(None)
+    }
+}
+
+// Converts `docs.rs` URL, or URL under other documentation base URL,
+// of the package configured in `rustdocifier` to rustdoc format.
+fn convert_url(url: &str, rustdocifier: &ReadmeRustdocifier) -> Result<String, Error> {
+    let package_name = &rustdocifier.package_name;
+    let version = rustdocifier.version.as_deref();
+    let crate_name = rustdocifier.crate_name.as_deref();
+    let docs_base_urls = &rustdocifier.docs_base_urls;
+    let disambiguators = rustdocifier.disambiguators;
+
+    let docs_url = match DocsUrl::parse(url, package_name, crate_name, docs_base_urls) {
+        Some(docs_url) => docs_url,
+        None => return Ok(url.to_owned()),
+    };
+    let fragment = docs_url.fragment.as_deref();
+
+    // VERSION
+
+    if docs_url.layout.has_version() {
+        if let Some(version) = version {
+            let url_version = docs_url.version();
+            if !is_accepted_version(url_version, version, rustdocifier.version_policy) {
+                return Err(match url_version {
+                    Some(_) => Error::WrongVersionInUrl(url.to_owned()),
+                    None => Error::MissingVersionInUrl(url.to_owned()),
+                });
+            }
+        }
+    }
+
+    match docs_url.kind {
+        DocsUrlKind::Rustdoc => {}
+        // e.g. `https://docs.rs/crate/foo/0.1.0/features`, which has no rustdoc equivalent
+        DocsUrlKind::CratePage => return Ok(url.to_owned()),
+        DocsUrlKind::Source => {
+            return convert_source_url(
+                url,
+                &docs_url,
+                crate_name,
+                rustdocifier.source_base_url.as_deref(),
+            )
+        }
+    }
+
+    let path = docs_url.crate_path();
+
+    if docs_url.query.is_some() {
+        // e.g. search `https://docs.rs/foo/0.1.0/foo/?search=bar`, which has no rustdoc equivalent
+        if let (Some(crate_name), Some(url_crate)) = (crate_name, path.first()) {
+            if url_crate != crate_name {
+                return Err(Error::WrongCrateNameInUrl(url.to_owned()));
+            }
+        }
+        return Ok(url.to_owned());
+    }
+
+    if path == ["index.html"] {
+        return Ok(root_link(fragment));
+    }
+
+    // CRATE NAME
+
+    if path.is_empty() {
+        // NO CRATE NAME IN URL
+
+        return Ok(root_link(fragment));
+    }
+
+    let url_crate = &path[0];
+
+    if let Some(crate_name) = crate_name {
+        if url_crate != crate_name {
+            return Err(Error::WrongCrateNameInUrl(url.to_owned()));
+        }
+    }
+
+    if path.len() == 2 && path[1] == "index.html" {
+        return Ok(root_link(fragment));
+    }
+
+    // FILENAME
+
+    if path.len() == 1 {
+        // NO FILENAME IN URL
+
+        return Ok(root_link(fragment));
+    }
+
+    let last = path.last().unwrap().as_str();
+
+    let (modules, filename) = if last.contains('.') {
+        (&path[1..path.len() - 1], last)
+    } else {
+        (&path[1..], "index.html")
+    };
+
+    // e.g. `struct.STRUCT.html#method.METHOD` or `fn.FUNCTION.html#fragment`
+    let item = if filename == "index.html" {
+        None
+    } else {
+        Some(docs_urls::item_page(filename).ok_or_else(|| Error::UnrecognizedUrl(url.to_owned()))?)
+    };
+
+    // e.g. `struct.A%3CT%3E.html` isn't an item page which rustdoc generates
+    let names = path[..1]
+        .iter()
+        .chain(modules)
+        .map(String::as_str)
+        .chain(item.map(|(_, name)| name));
+    for name in names {
+        if !intra_doc_links::is_identifier(name) {
+            return Err(Error::UnrecognizedUrl(url.to_owned()));
+        }
+    }
+
+    Ok(docs_urls::intra_doc_path(
+        modules,
+        item,
+        fragment,
+        disambiguators,
+    ))
+}
+
+// Checks crate name of source URL like `https://docs.rs/foo/0.1.0/src/foo/a.rs.html#10-20`
+// and converts it to `SOURCE_BASE_URL/a.rs#L10-L20`, if source base URL is given.
+fn convert_source_url(
+    url: &str,
+    docs_url: &DocsUrl,
+    crate_name: Option<&str>,
+    source_base_url: Option<&str>,
+) -> Result<String, Error> {
+    // skip `src`
+    let path = &docs_url.crate_path()[1..];
+
+    if let (Some(crate_name), Some(url_crate)) = (crate_name, path.first()) {
+        if *url_crate != crate_name {
+            return Err(Error::WrongCrateNameInUrl(url.to_owned()));
+        }
+    }
+
+    let file = match path
+        .get(1..)
+        .unwrap_or_default()
+        .join("/")
+        .strip_suffix(".html")
+    {
+        Some(file) => file.to_owned(),
+        None => return Ok(url.to_owned()),
+    };
+    let source_base_url = match source_base_url {
+        Some(source_base_url) => source_base_url,
+        None => return Ok(url.to_owned()),
+    };
+
+    let is_line_number = |text: &str| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit());
+    match docs_url.fragment.as_deref() {
+        Some(fragment) if is_line_number(fragment) => {
+            Ok(format!("{}/{}#L{}", source_base_url, file, fragment))
+        }
+        Some(fragment) => match fragment.split_once('-') {
+            Some((first, last)) if is_line_number(first) && is_line_number(last) => {
+                Ok(format!("{}/{}#L{}-L{}", source_base_url, file, first, last))
+            }
+            _ => Ok(format!("{}/{}#{}", source_base_url, file, fragment)),
+        },
+        None => Ok(format!("{}/{}", source_base_url, file)),
+    }
+}
+
+fn env_var(name: &str) -> Result<String, Error> {
+    env::var(name).map_err(|_| Error::MissingEnvVar(name.to_owned()))
+}
+
+// Writes `contents` to file, unless the file already has same contents,
+// so that modification time of the file changes only when needed.
+fn write_if_changed(path: &Path, contents: &str) -> Result<(), Error> {
+    if fs::read(path).is_ok_and(|old| old == contents.as_bytes()) {
+        return Ok(());
+    }
+    fs::write(path, contents)
+        .map_err(|error| Error::IoFailed(format!("{}: {}", path.display(), error)))
+}
+
+// Returns `true` if link label is a single code span like `` `foo` ``.
+fn is_code_span_label(label: &str) -> bool {
+    label.starts_with('`') && code_span_len(label) == label.len()
+}
+
+// Returns length of code span at start of given text
+// or, if there is no code span, length of backtick run.
+fn code_span_len(text: &str) -> usize {
+    let backtick_count = text.len() - text.trim_start_matches('`').len();
+    let mut pos = backtick_count;
+    while let Some(offset) = text[pos..].find('`') {
+        let start = pos + offset;
+        let run_len = text[start..].len() - text[start..].trim_start_matches('`').len();
+        if run_len == backtick_count {
+            return start + run_len;
+        }
+        pos = start + run_len;
+    }
+    backtick_count
+}
+
+fn is_code_block_end(line: &str, backtick_count: usize) -> bool {
+    if line.len() < backtick_count {
+        false
+    } else {
+        for n in 0..backtick_count {
+            if line.as_bytes()[n] != b'`' {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn is_code_block_start(line: &str) -> Option<usize> {
+    let mut backtick_count = 0;
+    while backtick_count < line.len() && line.as_bytes()[backtick_count] == b'`' {
+        backtick_count += 1;
+    }
+
+    if backtick_count >= 3 {
+        Some(backtick_count)
+    } else {
+        None
+    }
+}
+
+// Returns `true` if version in URL, or no version if `None`,
+// is accepted for package `version` with given policy.
+fn is_accepted_version(url_version: Option<&str>, version: &str, policy: VersionPolicy) -> bool {
+    let is_wildcard = matches!(url_version, None | Some("*") | Some("latest"));
+    let is_exact = match (
+        url_version.and_then(semver::Version::parse),
+        semver::Version::parse(version),
+    ) {
+        (Some(url_version), Some(version)) => url_version == version,
+        _ => url_version == Some(version),
+    };
+    // full versions like `0.3.1` must be exact, though those are also requirements
+    let is_full_version = url_version.and_then(semver::Version::parse).is_some();
+    let is_compatible = || match (url_version, semver::Version::parse(version)) {
+        (Some(_), _) if is_full_version => false,
+        (Some(url_version), Some(version)) if !is_wildcard => {
+            semver::Requirement::parse(url_version).is_some_and(|req| req.matches(&version))
+        }
+        (_, Some(version)) => semver::Requirement::Any.matches(&version),
+        _ => false,
+    };
+
+    match policy {
+        VersionPolicy::Exact => is_exact,
+        VersionPolicy::ExactOrLatest => is_exact || is_wildcard,
+        VersionPolicy::Compatible => is_exact || is_compatible(),
+        VersionPolicy::CompatibleWithoutWildcards => is_exact || (!is_wildcard && is_compatible()),
+    }
+}
+
+// Returns `true` if URL is for an item page like `struct.Foo.html`.
+fn is_item_url(url: &str) -> bool {
+    let path = url.split('#').next().unwrap_or(url);
+    let filename = path.rsplit('/').next().unwrap_or(path);
+    filename.ends_with(".html") && filename != "index.html"
+}
+
+// Returns line ending of given line: `"\r\n"`, `"\n"` or `""`.
+fn line_ending(line: &str) -> &str {
+    if line.ends_with("\r\n") {
+        "\r\n"
+    } else if line.ends_with('\n') {
+        "\n"
+    } else {
+        ""
+    }
+}
+
+fn root_link(fragment: Option<&str>) -> String {
+    if let Some(fragment) = fragment {
+        format!("crate#{}", fragment)
+    } else {
+        "crate".to_owned()
+    }
+}
+
+// ======================================================================
+// DATA
+
+// Base URLs of `docs.rs`, which are recognized by default.
+const DEFAULT_DOCS_BASE_URLS: &[&str] = &[
+    "https://docs.rs",
+    "http://docs.rs",
+    "https://www.docs.rs",
+    "http://www.docs.rs",
+];
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ============================================================
+    // HELPERS
+
+    fn test(input: &str, expected: &str) {
+        assert_eq!(
+            rustdocify(input, "foo", None, Some("foo")),
+            Ok(expected.to_owned())
+        );
+    }
+
+    // ============================================================
+    // MISC
+
+    #[test]
+    fn retain_exact_newlines() {
+        let input = "## A\n## B\r\n[x]: https://docs.rs/foo\n[y]: https://docs.rs/foo\r\na\nb\r\n";
+        let expected = "# A\n# B\r\n[x]: crate\n[y]: crate\r\na\nb\r\n";
+        assert_eq!(rustdocify(input, "foo", None, None).unwrap(), expected);
+    }
+
+    // ============================================================
+    // ALERTS
+
+    #[test]
+    fn alert_warning() {
+        test(
+            "> [!WARNING]\n> ## a\n>\n> [b]: https://docs.rs/foo\nc",
+            "<div class=\"warning\">\n\n# a\n\n[b]: crate\nc\n\n</div>",
+        );
+    }
+
+    #[test]
+    fn alert_caution() {
+        test(
+            "> [!CAUTION]\n> a",
+            "<div class=\"warning\">\n\na\n\n</div>",
+        );
+    }
+
+    #[test]
+    fn alert_note() {
+        test(
+            "> [!NOTE]\n> ## a\n> [b]: https://docs.rs/foo\n\nc",
+            "> **Note:**\n> # a\n> [b]: crate\n\nc",
+        );
+    }
+
+    #[test]
+    fn alert_with_lazy_continuation_lines() {
+        test(
+            "> [!NOTE]\n> a\nb\n> c\nd\n\ne",
+            "> **Note:**\n> a\n> b\n> c\n> d\n\ne",
+        );
+        test(
+            "> [!WARNING]\n> a\nb\n## c",
+            "<div class=\"warning\">\n\na\nb\n\n</div>\n# c",
+        );
+    }
+
+    #[test]
+    fn alert_with_code_block() {
+        test(
+            "> [!TIP]\n> ```\n> ## a\n> ```\n",
+            "> **Tip:**\n> ```\n> ## a\n> ```\n",
+        );
+    }
+
+    #[test]
+    fn alert_with_header_markers_at_end_of_input() {
+        test("> [!NOTE]\n> ##", "> **Note:**\n> ##");
+    }
+
+    #[test]
+    fn alert_in_code_block() {
+        test("```\n> [!NOTE]\n> ## a\n```", "```\n> [!NOTE]\n> ## a\n```");
+    }
+
+    #[test]
+    fn ignore_normal_blockquote() {
+        test("> [x]\n> ## a", "> [x]\n> ## a");
+    }
+
+    // ============================================================
+    // CHECK
+
+    fn check(input: &str) -> Vec<Warning> {
+        ReadmeRustdocifier::new("foo").check(input).unwrap()
+    }
+
+    #[test]
+    fn check_code_blocks() {
+        assert_eq!(
+            check(
+                "```
+$ cargo run
+```
+
+```rust,ignore,no_run
+```
+"
+            ),
+            [
+                Warning {
+                    line: 1,
+                    kind: WarningKind::UntaggedNonRustCodeBlock,
+                },
+                Warning {
+                    line: 5,
+                    kind: WarningKind::ConflictingCodeBlockAttributes(
+                        "ignore".to_owned(),
+                        "no_run".to_owned()
+                    ),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn check_unknown_fragments() {
+        assert_eq!(
+            ReadmeRustdocifier::new("foo")
+                .severity(Check::UnusedLinkDefinition, Severity::Allow)
+                .rustdocify_with_warnings(concat!(
+                "[a]: https://docs.rs/foo/*/foo/struct.A.html#method.new\n",
+                "[b]: https://docs.rs/foo/*/foo/struct.A.html#impl-Clone-for-A\n",
+                "[c]: https://docs.rs/foo/*/foo/a/#b\n",
+                "<a href=\"https://docs.rs/foo/*/foo/fn.b.html#x\">b</a>\n",
+            )),
+            Ok((
+                "[a]: crate::A::new\n[b]: crate::A#impl-Clone-for-A\n[c]: crate::a#b\n[b](crate::b#x)\n"
+                    .to_owned(),
+                vec![
+                    Warning {
+                        line: 2,
+                        kind: WarningKind::UnknownFragment(
+                            "https://docs.rs/foo/*/foo/struct.A.html#impl-Clone-for-A".to_owned()
+                        ),
+                    },
+                    Warning {
+                        line: 4,
+                        kind: WarningKind::UnknownFragment(
+                            "https://docs.rs/foo/*/foo/fn.b.html#x".to_owned()
+                        ),
+                    },
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn check_severities() {
+        let input = "```\n$ cargo run\n```\n\n```rust,no-run\n```\n";
+        let rustdocifier = ReadmeRustdocifier::new("foo")
+            .severity(Check::UntaggedNonRustCodeBlock, Severity::Deny)
+            .severity(Check::UntaggedNonRustCodeBlock, Severity::Allow);
+        assert_eq!(
+            rustdocifier.check(input),
+            Ok(vec![Warning {
+                line: 5,
+                kind: WarningKind::UnknownCodeBlockAttribute("no-run".to_owned()),
+            }])
+        );
+        assert_eq!(
+            rustdocifier
+                .severity(Check::UnknownCodeBlockAttribute, Severity::Deny)
+                .rustdocify(input),
+            Err(Error::DeniedWarning(Warning {
+                line: 5,
+                kind: WarningKind::UnknownCodeBlockAttribute("no-run".to_owned()),
+            }))
+        );
+    }
+
+    #[test]
+    fn check_code_block_within_alert() {
+        assert_eq!(
+            check(
+                "# foo
+
+> [!NOTE]
+> a
+> ```rust,no-run
+> ```
+"
+            ),
+            [Warning {
+                line: 5,
+                kind: WarningKind::UnknownCodeBlockAttribute("no-run".to_owned()),
+            }]
+        );
+    }
+
+    #[test]
+    fn check_ignores_rewritten_info() {
+        assert_eq!(
+            ReadmeRustdocifier::new("foo")
+                .code_block_info("", "text")
+                .check(
+                    "```
+$ cargo run
+```
+"
+                ),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn check_dependencies() {
+        let manifest = "[package]\nname = \"foo\"\n[dev-dependencies]\nrand = \"0.8\"\n";
+        assert_eq!(
+            ReadmeRustdocifier::new("foo")
+                .manifest(manifest)
+                .check_dependencies(true)
+                .check("# foo\n\n```rust\nuse foo::A;\nlet a = rand::random();\ntokio::spawn(a);\n```\n```toml\nb::c\n```\n"),
+            Ok(vec![Warning {
+                line: 6,
+                kind: WarningKind::UndeclaredDependency("tokio".to_owned()),
+            }])
+        );
+    }
+
+    #[test]
+    fn check_dependencies_without_manifest() {
+        assert_eq!(
+            ReadmeRustdocifier::new("foo")
+                .check_dependencies(true)
+                .check(""),
+            Err(Error::MissingManifest(
+                "ReadmeRustdocifier::check_dependencies".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn check_link_references() {
+        assert_eq!(
+            check(concat!(
+                "See [a], [b][B  C], [d][] and [e][].\n",
+                "\n",
+                "[A]: https://example.com/a\n",
+                "[b c]: https://example.com/b\n",
+                "[f]: https://example.com/f\n",
+            )),
+            [
+                Warning {
+                    line: 1,
+                    kind: WarningKind::UndefinedLinkReference("d".to_owned()),
+                },
+                Warning {
+                    line: 1,
+                    kind: WarningKind::UndefinedLinkReference("e".to_owned()),
+                },
+                Warning {
+                    line: 5,
+                    kind: WarningKind::UnusedLinkDefinition("f".to_owned()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn check_duplicate_link_definitions() {
+        assert_eq!(
+            check("[a]\n\n[a]: https://example.com/1\n[A]: https://example.com/2\n"),
+            [Warning {
+                line: 4,
+                kind: WarningKind::DuplicateLinkDefinition("A".to_owned()),
+            }]
+        );
+    }
+
+    #[test]
+    fn check_link_references_ignores_code_and_html() {
+        assert_eq!(
+            check(concat!(
+                "`[a]` \\[b] [c](https://example.com) [^1]\n",
+                "- [x] done\n",
+                "\n",
+                "```text\n",
+                "[d]\n",
+                "```\n",
+                "\n",
+                "<div>\n",
+                "[e]\n",
+                "</div>\n",
+                "\n",
+                "> [!NOTE]\n",
+                "> [f]\n",
+                "\n",
+                "[f]: https://example.com\n",
+            )),
+            []
+        );
+    }
+
+    #[test]
+    fn check_link_references_ignores_html_comments() {
+        assert_eq!(
+            check(concat!(
+                "<!--\n",
+                "[a] b\n",
+                "\n",
+                "[c]: https://example.com/c\n",
+                "-->\n",
+                "d <!-- [e] --> f <!-- [g]\n",
+                "[h]\n",
+                "--> i\n",
+            )),
+            []
+        );
+    }
+
+    #[test]
+    fn check_link_references_ignores_text_in_brackets() {
+        assert_eq!(
+            check("[WIP] See [0, 1], [RFC 1234], [`a b`] and [c][RFC 1234].\n"),
+            [
+                Warning {
+                    line: 1,
+                    kind: WarningKind::UndefinedLinkReference("`a b`".to_owned()),
+                },
+                Warning {
+                    line: 1,
+                    kind: WarningKind::UndefinedLinkReference("RFC 1234".to_owned()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn check_rustdoc_only_links() {
+        assert_eq!(
+            ReadmeRustdocifier::new("foo")
+                .version("0.1.0")
+                .check(concat!(
+                    "See [bar](crate::bar()), [`Foo::new`] and [`Baz`].\n",
+                    "\n",
+                    "[`Baz`]: struct@crate::Baz\n",
+                )),
+            Ok(vec![
+                Warning {
+                    line: 1,
+                    kind: WarningKind::RustdocOnlyLink(
+                        "crate::bar()".to_owned(),
+                        "https://docs.rs/foo/0.1.0/foo/fn.bar.html".to_owned()
+                    ),
+                },
+                Warning {
+                    line: 1,
+                    kind: WarningKind::RustdocOnlyLink(
+                        "`Foo::new`".to_owned(),
+                        "https://docs.rs/foo/0.1.0/foo/?search=Foo::new".to_owned()
+                    ),
+                },
+                Warning {
+                    line: 3,
+                    kind: WarningKind::RustdocOnlyLink(
+                        "struct@crate::Baz".to_owned(),
+                        "https://docs.rs/foo/0.1.0/foo/struct.Baz.html".to_owned()
+                    ),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn check_portable_links() {
+        assert_eq!(
+            check(concat!(
+                "See [`Foo`], [bar](https://docs.rs/foo/*/foo/fn.bar.html) and [baz][].\n",
+                "\n",
+                "[`Foo`]: https://docs.rs/foo/*/foo/struct.Foo.html\n",
+            )),
+            [Warning {
+                line: 1,
+                kind: WarningKind::UndefinedLinkReference("baz".to_owned()),
+            }]
+        );
+    }
+
+    // ============================================================
+    // DOCS.RS URLS
+
+    #[test]
+    fn docs_rs_url_is_inverse_of_conversion() {
+        for (path, kind) in [
+            ("crate", ItemKind::Module),
+            ("crate::a::b", ItemKind::Module),
+            ("crate::A", ItemKind::Enum),
+            ("crate::A::new", ItemKind::EnumMethod),
+            ("crate::a::A::B", ItemKind::EnumVariant),
+            ("crate::a::f", ItemKind::Function),
+            ("crate::m", ItemKind::Macro),
+            ("crate::S", ItemKind::Struct),
+            ("crate::a::S::new", ItemKind::StructMethod),
+            ("crate::T", ItemKind::Trait),
+            ("crate::T::f", ItemKind::TraitMethod),
+        ] {
+            let url = docs_rs_url(path, kind, "foo-bar", Some("0.1.0"), None).unwrap();
+            assert_eq!(
+                rustdocify(&format!("[x]: {}", url), "foo-bar", Some("0.1.0"), None),
+                Ok(format!("[x]: {}", path)),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn docs_rs_url_invalid_path() {
+        assert_eq!(
+            docs_rs_url("Foo", ItemKind::StructMethod, "foo", None, None),
+            Err(Error::InvalidIntraDocPath("Foo".to_owned()))
+        );
+        assert_eq!(
+            docs_rs_url("crate::", ItemKind::Struct, "foo", None, None),
+            Err(Error::InvalidIntraDocPath("crate::".to_owned()))
+        );
+        assert_eq!(
+            docs_rs_url("std::vec::Vec", ItemKind::Struct, "foo", None, None),
+            Err(Error::InvalidIntraDocPath("std::vec::Vec".to_owned()))
+        );
+        assert_eq!(
+            docs_rs_url("fn@foo()", ItemKind::Function, "foo", None, Some("bar")),
+            Ok("https://docs.rs/foo/latest/bar/fn.foo.html".to_owned())
+        );
+    }
+
+    #[test]
+    fn docs_rs_url_parse() {
+        let url: DocsRsUrl = "https://docs.rs/foo-bar/%5E0.1/foo_bar/a/b/enum.A.html#variant.B"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            url,
+            DocsRsUrl {
+                package: "foo-bar".to_owned(),
+                version: Some("^0.1".to_owned()),
+                crate_name: Some("foo_bar".to_owned()),
+                modules: vec!["a".to_owned(), "b".to_owned()],
+                kind: ItemKind::Enum,
+                name: Some("A".to_owned()),
+                fragment: Some("variant.B".to_owned()),
+            }
+        );
+        assert_eq!(url.intra_doc_path(), "crate::a::b::A::B");
+
+        let url: DocsRsUrl = "http://www.docs.rs/foo#x".parse().unwrap();
+        assert_eq!(
+            (url.version.as_deref(), url.crate_name.as_deref()),
+            (None, None)
+        );
+        assert_eq!(url.intra_doc_path(), "crate#x");
+
+        let url: DocsRsUrl = "https://docs.rs/foo/0.1.0/foo/x/struct.A.html#y%20z"
+            .parse()
+            .unwrap();
+        assert_eq!(url.fragment.as_deref(), Some("y z"));
+        assert_eq!(url.intra_doc_path(), "crate::x::A#y%20z");
+    }
+
+    #[test]
+    fn docs_rs_url_parse_unrecognized() {
+        for url in [
+            "https://example.com/foo",
+            "https://docs.rs/",
+            "https://docs.rs/crate/foo/0.1.0",
+            "https://docs.rs/foo/0.1.0/src/foo/lib.rs.html",
+            "https://docs.rs/foo/0.1.0/foo/?search=a",
+            "https://docs.rs/foo/0.1.0/foo/constant.A.html",
+            "https://docs.rs/foo/0.1.0/foo/a.txt",
+            "https://docs.rs/foo/0.1.0/foo/struct.A%3CT%3E.html",
+            "https://docs.rs/foo/0.1.0/foo/a%2Fb/struct.A.html",
+            "https://docs.rs/foo/0.1.0/foo/a b/",
+            "https://docs.rs/foo/0.1.0/foo%3A%3Abar/",
+            "https://docs.rs/fo%2Fo/0.1.0/foo/",
+        ] {
+            assert_eq!(
+                url.parse::<DocsRsUrl>(),
+                Err(Error::UnrecognizedUrl(url.to_owned()))
+            );
+        }
+    }
+
+    #[test]
+    fn docs_rs_url_display() {
+        for url in [
+            "https://docs.rs/foo",
+            "https://docs.rs/foo/0.1.0",
+            "https://docs.rs/foo/0.1.0/foo/",
+            "https://docs.rs/foo/0.1.0/foo/a/b/#x",
+            "https://docs.rs/foo/*/foo/a/struct.A.html#method.new",
+            "https://docs.rs/foo/latest/foo/trait.A.html",
+        ] {
+            assert_eq!(url.parse::<DocsRsUrl>().unwrap().to_string(), url);
+        }
+        assert_eq!(
+            "https://docs.rs/foo/0.1.0/foo/a/index.html"
+                .parse::<DocsRsUrl>()
+                .unwrap()
+                .to_string(),
+            "https://docs.rs/foo/0.1.0/foo/a/"
+        );
+    }
+
+    #[test]
+    fn docs_rs_url_round_trip() {
+        for url in [
+            "https://docs.rs/foo/%5E0.1/foo/struct.A.html",
+            "https://docs.rs/foo/0.1.0/foo/struct.A.html#impl-From%3C&str%3E-for-A",
+            "https://docs.rs/foo/0.1.0/foo/struct.A%C3%A4.html#a%20b%23c%25",
+            "https://docs.rs/foo/0.1.0/foo/_a/fn.b_1.html#x/y?z",
+        ] {
+            let parsed: DocsRsUrl = url.parse().unwrap();
+            assert_eq!(parsed.to_string(), url);
+            assert_eq!(parsed.to_string().parse::<DocsRsUrl>(), Ok(parsed));
+        }
+
+        let url: DocsRsUrl = "https://docs.rs/foo/%3C1/foo/#a%20b".parse().unwrap();
+        assert_eq!(url.version.as_deref(), Some("<1"));
+        assert_eq!(url.fragment.as_deref(), Some("a b"));
+        assert_eq!(url.to_string(), "https://docs.rs/foo/%3C1/foo/#a%20b");
+    }
+
+    #[test]
+    fn docs_rs_url_matches_conversion() {
+        for url in [
+            "https://docs.rs/foo",
+            "https://docs.rs/foo/0.1.0/foo/index.html#x",
+            "https://docs.rs/foo/0.1.0/foo/a/b",
+            "https://docs.rs/foo/0.1.0/foo/a/fn.f.html#y",
+            "https://docs.rs/foo/0.1.0/foo/trait.T.html#tymethod.f",
+            "https://docs.rs/foo/0.1.0/foo/struct.S.html#method.new",
+        ] {
+            assert_eq!(
+                rustdocify(&format!("[x]: {}", url), "foo", None, None),
+                Ok(format!(
+                    "[x]: {}",
+                    url.parse::<DocsRsUrl>().unwrap().intra_doc_path()
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn add_missing_link_definitions() {
+        let rustdocifier = ReadmeRustdocifier::new("foo").version("0.1.0");
+        assert_eq!(
+            rustdocifier.add_missing_link_definitions(concat!(
+                "See [`A`], [`f()`], [b] and [c](crate::c()).\n",
+                "\n",
+                "[`A`]: https://docs.rs/foo/0.1.0/foo/struct.A.html",
+            )),
+            Ok(concat!(
+                "See [`A`], [`f()`], [b] and [c](crate::c()).\n",
+                "\n",
+                "[`A`]: https://docs.rs/foo/0.1.0/foo/struct.A.html\n",
+                "[`f()`]: https://docs.rs/foo/0.1.0/foo/fn.f.html\n",
+            )
+            .to_owned())
+        );
+        assert_eq!(
+            rustdocifier.add_missing_link_definitions("[`enum@B`] and [`enum@b`]\n"),
+            Ok(
+                "[`enum@B`] and [`enum@b`]\n\n[`enum@B`]: https://docs.rs/foo/0.1.0/foo/enum.B.html\n"
+                    .to_owned()
+            )
+        );
+        // item kinds aren't guessed from naming conventions
+        assert_eq!(
+            rustdocifier.add_missing_link_definitions("[`B`], [`b`] and [`B::new`]\n"),
+            Ok("[`B`], [`b`] and [`B::new`]\n".to_owned())
+        );
+        assert_eq!(
+            rustdocifier.add_missing_link_definitions("[b]"),
+            Ok("[b]".to_owned())
+        );
+    }
+
+    #[test]
+    fn add_missing_link_definitions_with_denied_warnings() {
+        let rustdocifier = ReadmeRustdocifier::new("foo")
+            .severity(Check::RustdocOnlyLink, Severity::Deny)
+            .severity(Check::UnusedLinkDefinition, Severity::Deny);
+        assert_eq!(
+            rustdocifier.add_missing_link_definitions("[`f()`]\n"),
+            Ok("[`f()`]\n\n[`f()`]: https://docs.rs/foo/latest/foo/fn.f.html\n".to_owned())
+        );
+    }
+
+    // ============================================================
+    // BUILD SCRIPT
+
+    #[test]
+    fn from_env() {
+        // Cargo sets these also when running tests.
+        let rustdocifier = ReadmeRustdocifier::from_env().unwrap();
+        assert_eq!(rustdocifier.package_name, "readme-rustdocifier-core");
+        assert_eq!(
+            rustdocifier.version.as_deref(),
+            Some(env!("CARGO_PKG_VERSION"))
+        );
+        assert_eq!(
+            rustdocifier.crate_name.as_deref(),
+            Some("readme_rustdocifier_core")
+        );
+        assert_eq!(
+            rustdocifier.base_dir.as_deref(),
+            Some(Path::new(env!("CARGO_MANIFEST_DIR")))
+        );
+    }
+
+    #[test]
+    fn from_manifest_path() {
+        let dir = std::env::temp_dir().join("readme-rustdocifier-test-from-manifest-path");
+        fs::create_dir_all(dir.join("docs")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"foo\"\nversion = \"0.1.0\"\nreadme = \"docs/README.md\"\n[lib]\nname = \"bar\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("docs/README.md"),
+            "# foo\n\n[a]: https://docs.rs/foo/0.1.0/bar/struct.A.html\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            ReadmeRustdocifier::from_manifest_path(dir.join("Cargo.toml"))
+                .unwrap()
+                .rustdocify_readme(),
+            Ok("\n[a]: crate::A\n".to_owned())
+        );
+    }
+
+    #[test]
+    fn write_only_if_changed() {
+        let dir = std::env::temp_dir().join("readme-rustdocifier-test-write");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("README-rustdocified.md");
+        fs::write(&path, "a").unwrap();
+
+        let old_time = std::time::SystemTime::UNIX_EPOCH;
+        let modified = || fs::metadata(&path).unwrap().modified().unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old_time)
+            .unwrap();
+
+        write_if_changed(&path, "a").unwrap();
+        assert_eq!(modified(), old_time);
+        write_if_changed(&path, "b").unwrap();
+        assert_ne!(modified(), old_time);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "b");
+    }
+
+    // ============================================================
+    // MACROS
+
+    #[test]
+    fn convert_readme() {
+        let dir = std::env::temp_dir().join("readme-rustdocifier-test-convert-readme");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"foo\"\n").unwrap();
+        fs::write(
+            dir.join("README.md"),
+            "# foo\n<!-- rustdocify:embed a.rs -->\n<!-- rustdocify:embed a.rs -->\n",
+        )
+        .unwrap();
+        fs::write(dir.join("a.rs"), "a();\n").unwrap();
+
+        let conversion = ReadmeRustdocifier::from_manifest_path(dir.join("Cargo.toml"))
+            .unwrap()
+            .convert_readme()
+            .unwrap();
+        assert_eq!(
+            conversion.result,
+            "```rust\na();\n```\n```rust\na();\n```\n"
+        );
+        assert_eq!(
+            conversion.files,
+            [
+                dir.join("README.md"),
+                dir.join("Cargo.toml"),
+                dir.join("a.rs")
+            ]
+        );
+    }
+
+    #[test]
+    fn code_block_info() {
+        let input = "```rust\n## a\n```\n```\n```\n```toml\n```";
+        let expected = "```rust,no_run\n## a\n```\n```text\n```\n```toml\n```";
+        assert_eq!(
+            ReadmeRustdocifier::new("foo")
+                .code_block_info("rust", "rust,no_run")
+                .code_block_info("", "text")
+                .rustdocify(input),
+            Ok(expected.to_owned())
+        );
+    }
+
+    #[test]
+    fn code_block_attributes_marker() {
+        test(
+            "<!-- rustdocify:attributes compile_fail -->\n\n```rust\n```\n```rust\n```",
+            "\n```rust,compile_fail\n```\n```rust\n```",
+        );
+    }
+
+    // ============================================================
+    // CODE BLOCK HIDDEN LINES
+
+    #[test]
+    fn code_block_hidden_lines() {
+        test(
+            "```\nuse a::B; // rustdoc:hidden\n```\n```toml\na = 1 // rustdoc:hidden\n```",
+            "```\n# use a::B;\n```\n```toml\na = 1 // rustdoc:hidden\n```",
+        );
+    }
+
+    #[test]
+    fn code_block_prelude() {
+        test(
+            "<!-- rustdocify:prelude\nuse a::B;\n\n-->\n```rust\nB;\n```\n```rust\n```",
+            "```rust\n# use a::B;\n#\nB;\n```\n```rust\n```",
+        );
+    }
+
+    #[test]
+    fn unused_code_block_prelude() {
+        let rustdocifier = ReadmeRustdocifier::new("foo");
+        assert_eq!(
+            rustdocifier.rustdocify_with_warnings(
+                "<!-- rustdocify:prelude\nuse a::B;\n-->\n```toml\n```\n```rust\nB;\n```\n\
+                 <!-- rustdocify:prelude\nuse c::D;\n-->\n\
+                 <!-- rustdocify:prelude\nuse e::F;\n-->\n"
+            ),
+            Ok((
+                "```toml\n```\n```rust\nB;\n```\n".to_owned(),
+                vec![
+                    Warning {
+                        line: 1,
+                        kind: WarningKind::UnusedPrelude,
+                    },
+                    Warning {
+                        line: 9,
+                        kind: WarningKind::UnusedPrelude,
+                    },
+                    Warning {
+                        line: 12,
+                        kind: WarningKind::UnusedPrelude,
+                    },
+                ]
+            ))
+        );
+    }
+
+    // ============================================================
+    // EMBED
+
+    #[test]
+    fn embed() {
+        let dir = std::env::temp_dir().join("readme-rustdocifier-test-embed");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("a.rs"),
+            "fn main() {\n    // ANCHOR: x\n    let a = 1; // rustdoc:hidden\n    // ANCHOR_END: x\n}\n",
+        )
+        .unwrap();
+
+        let input = concat!(
+            "<!-- rustdocify:embed a.rs region=x -->\n",
+            "```rust\n",
+            "old\n",
+            "```\n",
+            "<!-- rustdocify:embed a.rs -->\n",
+            "\n",
+        );
+        let expected = concat!(
+            "```rust\n",
+            "# let a = 1;\n",
+            "```\n",
+            "```rust\n",
+            "fn main() {\n",
+            "    # let a = 1;\n",
+            "}\n",
+            "```\n",
+            "\n",
+        );
+        let rustdocifier = ReadmeRustdocifier::new("foo").base_dir(&dir);
+        assert_eq!(rustdocifier.rustdocify(input), Ok(expected.to_owned()));
+        assert_eq!(
+            rustdocifier.check_embeds(true).rustdocify(input),
+            Err(Error::EmbedOutOfDate("a.rs:x".to_owned()))
+        );
+        assert_eq!(
+            ReadmeRustdocifier::new("foo")
+                .base_dir(&dir)
+                .convert(input)
+                .unwrap()
+                .files,
+            [dir.join("a.rs")]
+        );
+    }
+
+    #[test]
+    fn embed_missing_region() {
+        assert_eq!(
+            rustdocify(
+                "<!-- rustdocify:embed Cargo.toml region=foo -->",
+                "foo",
+                None,
+                None
+            ),
+            Err(Error::EmbedFailed(
+                "Cargo.toml:foo: region not found".to_owned()
+            ))
+        );
+    }
+
+    // ============================================================
+    // EMOJI SHORTCODES
+
+    #[test]
+    fn emoji_shortcodes() {
+        let input = "## :rocket: a\n:zap: `:zap:`\n```\n:zap:\n```\n[:zap:]: https://docs.rs/foo";
+        let expected = "# \u{1f680} a\n\u{26a1} `:zap:`\n```\n:zap:\n```\n[:zap:]: crate";
+        assert_eq!(
+            ReadmeRustdocifier::new("foo")
+                .emoji_shortcodes(true)
+                .rustdocify(input),
+            Ok(expected.to_owned())
+        );
+    }
+
+    #[test]
+    fn emoji_shortcodes_disabled_by_default() {
+        test(":rocket:", ":rocket:");
+    }
+
+    // ============================================================
+    // FEATURES
+
+    #[test]
+    fn features_marker() {
+        let input = "## A\n\n<!-- rustdocify:features -->\r\n\nfoo";
+        let expected = concat!(
+            "# A\n\n",
+            "| Feature | Enables | Description |\n",
+            "|---|---|---|\n",
+            "| `a` | `b` | A. |\n",
+            "| `b` |  |  |\r\n",
+            "\nfoo"
+        );
+        let manifest = "[features]\n# A.\na = [\"b\"]\nb = []";
+        assert_eq!(
+            ReadmeRustdocifier::new("foo")
+                .manifest(manifest)
+                .rustdocify(input),
+            Ok(expected.to_owned())
+        );
+    }
+
+    #[test]
+    fn features_marker_without_manifest() {
+        assert_eq!(
+            rustdocify("<!-- rustdocify:features -->\n", "foo", None, None),
+            Err(Error::MissingManifest(
+                "<!-- rustdocify:features -->\n".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn features_marker_in_code_block() {
+        test(
+            "```\n<!-- rustdocify:features -->\n```",
+            "```\n<!-- rustdocify:features -->\n```",
+        );
+    }
+
+    // ============================================================
+    // HEADER LINKS
+
+    #[test]
+    fn header_links() {
+        test(
+            "# Foo\n## Modules\n[a](#modules) [b](#foo)\n\n[c]: #modules\n",
+            "# Modules\n[a](#modules-1) [b](crate)\n\n[c]: #modules-1\n",
+        );
+    }
+
+    #[test]
+    fn header_link_in_alert() {
+        test(
+            "## A\n> [!NOTE]\n> [a](#a)\n",
+            "# A\n> **Note:**\n> [a](#a)\n",
+        );
+    }
+
+    #[test]
+    fn unknown_header_link() {
+        assert_eq!(
+            ReadmeRustdocifier::new("foo")
+                .rustdocify_with_warnings("## A\n[x](#b) [y][]\n\n[y]: #L10\n"),
+            Ok((
+                "# A\n[x](#b) [y][]\n\n[y]: #L10\n".to_owned(),
+                vec![
+                    Warning {
+                        line: 2,
+                        kind: WarningKind::UnknownHeaderLink("#b".to_owned()),
+                    },
+                    Warning {
+                        line: 4,
+                        kind: WarningKind::UnknownHeaderLink("#L10".to_owned()),
+                    },
+                ]
+            ))
+        );
+        assert_eq!(
+            rustdocify("[x](#user-content-a)", "foo", None, None),
+            Ok("[x](#user-content-a)".to_owned())
+        );
+        assert_eq!(
+            ReadmeRustdocifier::new("foo")
+                .severity(Check::UnknownHeaderLink, Severity::Deny)
+                .rustdocify("[x](#b)"),
+            Err(Error::DeniedWarning(Warning {
+                line: 1,
+                kind: WarningKind::UnknownHeaderLink("#b".to_owned()),
+            }))
+        );
+    }
+
+    #[test]
+    fn header_link_with_emoji_shortcode() {
+        assert_eq!(
+            ReadmeRustdocifier::new("foo")
+                .emoji_shortcodes(true)
+                .rustdocify("## :rocket: Fast\n[x](#rocket-fast)\n"),
+            Ok("# 🚀 Fast\n[x](#-fast)\n".to_owned())
+        );
+    }
+
+    // ============================================================
+    // HTML LINKS
+
+    #[test]
+    fn html_links() {
+        test(
+            concat!(
+                "<p align=\"center\">\n",
+                "<a href=\"https://docs.rs/foo/*/foo/struct.A.html\">A</a>\n",
+                "</p>\n",
+                "\n",
+                "See <a href=\"https://docs.rs/foo/*/foo/struct.A.html#method.new\">`A::new`</a>.\n",
+            ),
+            concat!(
+                "<p align=\"center\">\n",
+                "<a href=\"struct.A.html\">A</a>\n",
+                "</p>\n",
+                "\n",
+                "See [`A::new`](crate::A::new).\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn html_link_wrong_version() {
+        assert_eq!(
+            rustdocify(
+                "<a href=\"https://docs.rs/foo/0.2.0\">foo</a>",
+                "foo",
+                Some("0.1.0"),
+                None
+            ),
+            Err(Error::WrongVersionInUrl(
+                "https://docs.rs/foo/0.2.0".to_owned()
+            ))
+        );
+    }
+
+    // ============================================================
+    // HEADERS - ERRORS
+
+    #[test]
+    fn non_first_top_level_header() {
+        assert_eq!(
+            rustdocify("## Foo\n# Bar", "foo", None, None),
+            Err(Error::NonFirstTopLevelHeader("# Bar".to_owned()))
+        );
+    }
+
+    // ============================================================
+    // HEADERS - IGNORE
+
+    #[test]
+    fn ignore_headers_without_space_before() {
+        test("#Foo\n##Bar", "#Foo\n##Bar");
+    }
+
+    #[test]
+    fn ignore_header_markers_at_end_of_input() {
+        test("a\n##", "a\n##");
+        test("#", "#");
+    }
+
+    // ============================================================
+    // HEADERS
+
+    #[test]
+    fn convert_headers() {
+        test(
+            "# Foo\nfoo\n## Bar\nbar\n### Baz",
+            "foo\n# Bar\nbar\n## Baz",
+        );
+    }
+
+    // ============================================================
+    // LINKS - ERRORS
+
+    #[test]
+    fn missing_version_in_url() {
+        assert_eq!(
+            rustdocify("[x]: https://docs.rs/foo", "foo", Some("0.1.0"), None),
+            Err(Error::MissingVersionInUrl("https://docs.rs/foo".to_owned()))
+        );
+    }
+
+    #[test]
+    fn unrecognized_url_html() {
+        let input = "[x]: https://docs.rs/foo/*/foo/hello_world.html";
+        let expected = "https://docs.rs/foo/*/foo/hello_world.html".to_owned();
+        assert_eq!(
+            rustdocify(input, "foo", None, None),
+            Err(Error::UnrecognizedUrl(expected))
+        );
+    }
+
+    #[test]
+    fn unrecognized_url_non_identifier() {
+        for url in [
+            "https://docs.rs/foo/*/foo/struct.A%3CT%3E.html",
+            "https://docs.rs/foo/*/foo/a%20b/struct.A.html",
+            "https://docs.rs/foo/*/foo/a-b/",
+        ] {
+            assert_eq!(
+                rustdocify(&format!("[x]: {}", url), "foo", None, None),
+                Err(Error::UnrecognizedUrl(url.to_owned()))
+            );
+        }
+    }
+
+    #[test]
+    fn unrecognized_url_non_html() {
+        let input = "[x]: https://docs.rs/foo/*/foo/hello_world.png";
+        let expected = "https://docs.rs/foo/*/foo/hello_world.png".to_owned();
+        assert_eq!(
+            rustdocify(input, "foo", None, None),
+            Err(Error::UnrecognizedUrl(expected))
+        );
+    }
+
+    #[test]
+    fn wrong_crate_name_in_url() {
+        assert_eq!(
+            rustdocify("[x]: https://docs.rs/foo/*/bar", "foo", None, Some("foo")),
+            Err(Error::WrongCrateNameInUrl(
+                "https://docs.rs/foo/*/bar".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn wrong_version_in_url() {
+        assert_eq!(
+            rustdocify("[x]: https://docs.rs/foo/0.2.0", "foo", Some("0.1.0"), None),
+            Err(Error::WrongVersionInUrl(
+                "https://docs.rs/foo/0.2.0".to_owned()
+            ))
+        );
+    }
+
+    // ============================================================
+    // LINKS - VERSION POLICY
+
+    fn accepted(policy: VersionPolicy, version: &str) -> Vec<&'static str> {
+        [
+            "0.3.2",
+            "0.3.2+build.1",
+            "0.3",
+            "^0.3",
+            "~0.3.1",
+            "*",
+            "latest",
+            "",
+            "0.2",
+            "0.3.1",
+            "0.3.3",
+        ]
+        .into_iter()
+        .filter(|url_version| {
+            let url_version = Some(*url_version).filter(|v| !v.is_empty());
+            is_accepted_version(url_version, version, policy)
+        })
+        .collect()
+    }
+
+    #[test]
+    fn version_policy_exact() {
+        assert_eq!(
+            accepted(VersionPolicy::Exact, "0.3.2"),
+            ["0.3.2", "0.3.2+build.1"]
+        );
+    }
+
+    #[test]
+    fn version_policy_exact_or_latest() {
+        assert_eq!(
+            accepted(VersionPolicy::ExactOrLatest, "0.3.2"),
+            ["0.3.2", "0.3.2+build.1", "*", "latest", ""]
+        );
+    }
+
+    #[test]
+    fn version_policy_compatible() {
+        assert_eq!(
+            accepted(VersionPolicy::Compatible, "0.3.2"),
+            [
+                "0.3.2",
+                "0.3.2+build.1",
+                "0.3",
+                "^0.3",
+                "~0.3.1",
+                "*",
+                "latest",
+                ""
+            ]
+        );
+    }
+
+    #[test]
+    fn version_policy_compatible_full_version() {
+        assert!(!is_accepted_version(
+            Some("0.3.1"),
+            "0.3.2",
+            VersionPolicy::Compatible
+        ));
+        assert!(is_accepted_version(
+            Some("^0.3.1"),
+            "0.3.2",
+            VersionPolicy::Compatible
+        ));
+        assert!(is_accepted_version(
+            Some("1.2.0+build.1"),
+            "1.2.0",
+            VersionPolicy::CompatibleWithoutWildcards
+        ));
+    }
+
+    #[test]
+    fn version_policy_compatible_without_wildcards() {
+        assert_eq!(
+            accepted(VersionPolicy::CompatibleWithoutWildcards, "0.3.2"),
+            ["0.3.2", "0.3.2+build.1", "0.3", "^0.3", "~0.3.1"]
+        );
+    }
+
+    #[test]
+    fn version_policy_build_metadata() {
+        assert_eq!(
+            accepted(VersionPolicy::Exact, "0.3.2+build.2"),
+            ["0.3.2", "0.3.2+build.1"]
+        );
+    }
+
+    #[test]
+    fn version_policy_pre_release() {
+        assert_eq!(accepted(VersionPolicy::Compatible, "0.3.2-beta.1"), [""; 0]);
+        assert!(is_accepted_version(
+            Some("^0.3.2-beta"),
+            "0.3.2-beta.1",
+            VersionPolicy::Compatible
+        ));
+        assert!(!is_accepted_version(
+            Some("0.3.2-beta"),
+            "0.3.2-beta.1",
+            VersionPolicy::Compatible
+        ));
+        assert!(is_accepted_version(
+            Some("0.3.2-beta.1"),
+            "0.3.2-beta.1",
+            VersionPolicy::Exact
+        ));
+    }
+
+    #[test]
+    fn version_policy_non_semver() {
+        assert!(is_accepted_version(
+            Some("foo"),
+            "foo",
+            VersionPolicy::Exact
+        ));
+        assert!(!is_accepted_version(
+            Some("0.3"),
+            "foo",
+            VersionPolicy::Compatible
+        ));
+    }
+
+    #[test]
+    fn version_policy_links() {
+        let rustdocifier = ReadmeRustdocifier::new("foo")
+            .version("0.3.2")
+            .version_policy(VersionPolicy::Compatible);
+        assert_eq!(
+            rustdocifier.rustdocify("[x]: https://docs.rs/foo/^0.3/foo/fn.bar.html"),
+            Ok("[x]: crate::bar".to_owned())
+        );
+        assert_eq!(
+            rustdocifier.rustdocify("[x]: https://docs.rs/foo/0.2"),
+            Err(Error::WrongVersionInUrl(
+                "https://docs.rs/foo/0.2".to_owned()
+            ))
+        );
+        assert_eq!(
+            rustdocifier
+                .version_policy(VersionPolicy::CompatibleWithoutWildcards)
+                .rustdocify("[x]: https://docs.rs/foo"),
+            Err(Error::MissingVersionInUrl("https://docs.rs/foo".to_owned()))
+        );
+    }
+
+    // ============================================================
+    // LINKS - DOCS BASE URLS
+
+    #[test]
+    fn default_docs_base_urls() {
+        test(
+            concat!(
+                "[a]: http://docs.rs/foo/*/foo/struct.A.html\n",
+                "[b]: https://www.docs.rs/foo/*/foo/struct.B.html\n",
+                "[c]: https://docs.rs.example.com/foo/*/foo/struct.C.html\n",
+            ),
+            concat!(
+                "[a]: crate::A\n",
+                "[b]: crate::B\n",
+                "[c]: https://docs.rs.example.com/foo/*/foo/struct.C.html\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn versioned_docs_base_url() {
+        let rustdocifier = ReadmeRustdocifier::new("foo")
+            .version("0.1.0")
+            .docs_base_url("https://docs.example.com/foo/", DocsLayout::Versioned);
+        assert_eq!(
+            rustdocifier.rustdocify(concat!(
+                "[a]: https://docs.example.com/foo/0.1.0/foo/a/fn.b.html\n",
+                "<a href=\"https://docs.example.com/foo/0.1.0/foo/struct.A.html\" title=\"A\">A</a>\n",
+            )),
+            Ok(concat!(
+                "[a]: crate::a::b\n",
+                "<a href=\"struct.A.html\" title=\"A\">A</a>\n"
+            )
+            .to_owned())
+        );
+        assert_eq!(
+            rustdocifier.rustdocify("[a]: https://docs.example.com/foo/0.2.0/foo"),
+            Err(Error::WrongVersionInUrl(
+                "https://docs.example.com/foo/0.2.0/foo".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn cargo_doc_docs_base_url() {
+        let rustdocifier = ReadmeRustdocifier::new("foo-bar")
+            .version("0.1.0")
+            .docs_base_url("http://localhost:8000", DocsLayout::CargoDoc);
+        assert_eq!(
+            rustdocifier.rustdocify(concat!(
+                "[a]: http://localhost:8000/foo_bar/struct.A.html\n",
+                "[b]: http://localhost:8000/serde/trait.Serialize.html\n",
+            )),
+            Ok(concat!(
+                "[a]: crate::A\n",
+                "[b]: http://localhost:8000/serde/trait.Serialize.html\n",
+            )
+            .to_owned())
+        );
+    }
+
+    // ============================================================
+    // LINKS - CRATE PAGES AND SOURCE
+
+    #[test]
+    fn crate_page() {
+        let rustdocifier = ReadmeRustdocifier::new("foo").version("0.1.1");
+        let input =
+            "[a]: https://docs.rs/crate/foo/0.1.1\n[b]: https://docs.rs/crate/foo/0.1.1/features\n";
+        assert_eq!(rustdocifier.rustdocify(input), Ok(input.to_owned()));
+        assert_eq!(
+            rustdocifier.rustdocify("[a]: https://docs.rs/crate/foo/latest/features"),
+            Err(Error::WrongVersionInUrl(
+                "https://docs.rs/crate/foo/latest/features".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn source_url() {
+        let rustdocifier = ReadmeRustdocifier::new("foo")
+            .version("0.1.1")
+            .crate_name("foo");
+        let input = "[a]: https://docs.rs/foo/0.1.1/src/foo/lib.rs.html#10-20\n";
+        assert_eq!(rustdocifier.rustdocify(input), Ok(input.to_owned()));
+        assert_eq!(
+            rustdocifier.rustdocify("[a]: https://docs.rs/foo/0.1.0/src/foo/lib.rs.html"),
+            Err(Error::WrongVersionInUrl(
+                "https://docs.rs/foo/0.1.0/src/foo/lib.rs.html".to_owned()
+            ))
+        );
+        assert_eq!(
+            rustdocifier.rustdocify("[a]: https://docs.rs/foo/0.1.1/src/bar/lib.rs.html"),
+            Err(Error::WrongCrateNameInUrl(
+                "https://docs.rs/foo/0.1.1/src/bar/lib.rs.html".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn source_url_converted() {
+        let rustdocifier = ReadmeRustdocifier::new("foo")
+            .source_base_url("https://github.com/owner/foo/blob/v0.1.1/src")
+            .severity(Check::UnusedLinkDefinition, Severity::Allow);
+        assert_eq!(
+            rustdocifier.rustdocify_with_warnings(concat!(
+                "[a]: https://docs.rs/foo/0.1.1/src/foo/a/b.rs.html#7\n",
+                "[b]: https://docs.rs/foo/0.1.1/src/foo/lib.rs.html#x\n",
+                "<p><a href=\"https://docs.rs/foo/0.1.1/src/foo/lib.rs.html\">src</a></p>\n",
+            )),
+            Ok((
+                concat!(
+                    "[a]: https://github.com/owner/foo/blob/v0.1.1/src/a/b.rs#L7\n",
+                    "[b]: https://github.com/owner/foo/blob/v0.1.1/src/lib.rs#x\n",
+                    "<p><a href=\"https://github.com/owner/foo/blob/v0.1.1/src/lib.rs\">src</a></p>\n",
+                )
+                .to_owned(),
+                vec![]
+            ))
+        );
+    }
+
+    // ============================================================
+    // LINKS - DISAMBIGUATORS
+
+    #[test]
+    fn disambiguators() {
+        let rustdocifier = ReadmeRustdocifier::new("foo").disambiguators(true);
+        assert_eq!(
+            rustdocifier.rustdocify(concat!(
+                "[a]: https://docs.rs/foo/*/foo/\n",
+                "[b]: https://docs.rs/foo/*/foo/b/index.html\n",
+                "[c]: https://docs.rs/foo/*/foo/fn.c.html\n",
+                "[d]: https://docs.rs/foo/*/foo/macro.c.html\n",
+                "[e]: https://docs.rs/foo/*/foo/b/struct.E.html#method.new\n",
+                "[f]: https://docs.rs/foo/*/foo/enum.F.html#variant.G\n",
+            )),
+            Ok(concat!(
+                "[a]: crate\n",
+                "[b]: mod@crate::b\n",
+                "[c]: fn@crate::c\n",
+                "[d]: macro@crate::c\n",
+                "[e]: method@crate::b::E::new\n",
+                "[f]: variant@crate::F::G\n",
+            )
+            .to_owned())
+        );
+    }
+
+    #[test]
+    fn disambiguators_with_fragment() {
+        let rustdocifier = ReadmeRustdocifier::new("foo")
+            .disambiguators(true)
+            .severity(Check::UnusedLinkDefinition, Severity::Allow);
+        assert_eq!(
+            rustdocifier.rustdocify_with_warnings(concat!(
+                "[a]: https://docs.rs/foo/*/foo/trait.A.html#impl-Clone-for-A\n",
+                "<a href=\"https://docs.rs/foo/*/foo/struct.B.html\">B</a>\n",
+            )),
+            Ok((
+                "[a]: trait@crate::A#impl-Clone-for-A\n[B](struct@crate::B)\n".to_owned(),
+                vec![Warning {
+                    line: 1,
+                    kind: WarningKind::UnknownFragment(
+                        "https://docs.rs/foo/*/foo/trait.A.html#impl-Clone-for-A".to_owned()
+                    ),
+                }]
+            ))
+        );
+    }
+
+    // ============================================================
+    // LINKS - NOT LINK LINE
+
+    #[test]
+    fn no_close_bracket() {
+        test("[x: https://docs.rs/foo", "[x: https://docs.rs/foo");
+    }
+
+    #[test]
+    fn no_colon_after_close_bracket() {
+        test("[x] https://docs.rs/foo", "[x] https://docs.rs/foo");
+    }
+
+    #[test]
+    fn no_url() {
+        test("[x]:   ", "[x]:   ");
+    }
+
+    #[test]
+    fn text_after_url() {
+        test(
+            "[x]: https://docs.rs/foo   hello",
+            "[x]: https://docs.rs/foo   hello",
+        );
+    }
+
+    #[test]
+    fn no_close_angle_bracket() {
+        test(
+            "[x]: <https://docs.rs/foo/*/foo/struct.A.html",
+            "[x]: <https://docs.rs/foo/*/foo/struct.A.html",
+        );
+    }
+
+    #[test]
+    fn text_after_angle_brackets() {
+        test(
+            "[x]: <https://docs.rs/foo/*/foo/struct.A.html>x",
+            "[x]: <https://docs.rs/foo/*/foo/struct.A.html>x",
+        );
+    }
+
+    // ============================================================
+    // LINKS - SYNTAX
+
+    #[test]
+    fn angle_brackets() {
+        test(
+            "[x]: <https://docs.rs/foo/*/foo/struct.A.html>\n[y]: <https://example.com/a b>",
+            "[x]: crate::A\n[y]: <https://example.com/a b>",
+        );
+    }
+
+    #[test]
+    fn titles() {
+        test(
+            concat!(
+                "[x]: <https://docs.rs/foo/*/foo/struct.A.html> \"Title\"\n",
+                "[y]: https://docs.rs/foo/*/foo/fn.b.html   'Title <b>'\n",
+                "[z]: https://docs.rs/foo/*/foo/fn.c.html (Title)",
+            ),
+            concat!(
+                "[x]: crate::A \"Title\"\n",
+                "[y]: crate::b   'Title <b>'\n",
+                "[z]: crate::c (Title)",
+            ),
+        );
+    }
+
+    #[test]
+    fn indented_definitions() {
+        test(
+            "   [x]: https://docs.rs/foo\n\n    [y]: https://docs.rs/foo\n",
+            "   [x]: crate\n\n    [y]: https://docs.rs/foo\n",
+        );
+    }
+
+    #[test]
+    fn escaped_bracket_in_label() {
+        test("[a\\]b]: https://docs.rs/foo", "[a\\]b]: crate");
+    }
+
+    #[test]
+    fn definitions_in_containers() {
+        test(
+            concat!(
+                "> [a]: https://docs.rs/foo\n",
+                "\n",
+                "- b\n",
+                "- [c]: https://docs.rs/foo\n",
+                "\n",
+                "  [d]: https://docs.rs/foo\n",
+            ),
+            concat!(
+                "> [a]: crate\n",
+                "\n",
+                "- b\n",
+                "- [c]: crate\n",
+                "\n",
+                "  [d]: crate\n",
+            ),
+        );
+    }
+
+    #[test]
+    fn url_and_title_on_next_line() {
+        test(
+            "[a]:\n  https://docs.rs/foo/*/foo/struct.A.html\n  \"Title\"\n[b]: https://docs.rs/foo\n",
+            "[a]:\n  crate::A\n  \"Title\"\n[b]: crate\n",
+        );
+    }
+
+    #[test]
+    fn definition_does_not_interrupt_paragraph() {
+        test(
+            "a\n[x]: https://docs.rs/foo\n> b\n[y]: https://docs.rs/foo\n",
+            "a\n[x]: https://docs.rs/foo\n> b\n[y]: https://docs.rs/foo\n",
+        );
+    }
+
+    #[test]
+    fn definition_in_html_block() {
+        test(
+            "<div>\n[x]: https://docs.rs/foo\n</div>\n",
+            "<div>\n[x]: https://docs.rs/foo\n</div>\n",
+        );
+    }
+
+    #[test]
+    fn percent_encoded_url() {
+        test(
+            "[x]: https://docs.rs/foo/%2A/foo/struct.A.html#method.%6Eew",
+            "[x]: crate::A::new",
+        );
+    }
+
+    #[test]
+    fn search_url() {
+        test(
+            "[x]: https://docs.rs/foo/?search=bar\n[y]: https://docs.rs/foo/*/foo/?search=bar#x",
+            "[x]: https://docs.rs/foo/?search=bar\n[y]: https://docs.rs/foo/*/foo/?search=bar#x",
+        );
+        assert_eq!(
+            rustdocify(
+                "[x]: https://docs.rs/foo/*/bar/?search=baz",
+                "foo",
+                None,
+                Some("foo")
+            ),
+            Err(Error::WrongCrateNameInUrl(
+                "https://docs.rs/foo/*/bar/?search=baz".to_owned()
+            ))
+        );
+        assert_eq!(
+            rustdocify(
+                "[x]: https://docs.rs/foo/?search=bar",
+                "foo",
+                Some("0.1.0"),
+                None
+            ),
+            Err(Error::MissingVersionInUrl(
+                "https://docs.rs/foo/?search=bar".to_owned()
+            ))
+        );
+    }
+
+    // ============================================================
+    // LINKS - IGNORE
+
+    #[test]
+    fn ignore_link_of_another_domain() {
+        test(
+            "[x]: https://example.com/foo",
+            "[x]: https://example.com/foo",
+        );
+    }
+
+    #[test]
+    fn ignore_link_to_another_package() {
+        test("[x]: https://docs.rs/bar", "[x]: https://docs.rs/bar");
+    }
+
+    #[test]
+    fn ignore_link_to_another_package_which_has_this_package_as_prefix() {
+        test("[x]: https://docs.rs/foobar", "[x]: https://docs.rs/foobar");
+    }
+
+    // ============================================================
+    // LINKS - MISC
+
+    #[test]
+    fn retain_multiple_whitespace_before_url() {
+        test("[x]:   https://docs.rs/foo", "[x]:   crate");
+    }
+
+    #[test]
+    fn no_space_after_colon() {
+        test("[x]:https://docs.rs/foo", "[x]:crate");
+    }
+
+    // ============================================================
+    // LINKS - NO VERSION
+
+    #[test]
+    fn package() {
+        test("[x]: https://docs.rs/foo", "[x]: crate");
+    }
+
+    #[test]
+    fn package_fragment() {
+        test("[x]: https://docs.rs/foo#fragment", "[x]: crate#fragment");
+    }
+
+    #[test]
+    fn package_slash() {
+        test("[x]: https://docs.rs/foo/", "[x]: crate");
+    }
+
+    #[test]
+    fn package_slash_fragment() {
+        test("[x]: https://docs.rs/foo/#fragment", "[x]: crate#fragment");
+    }
+
+    // ============================================================
+    // LINKS - HAS VERSION, NO CRATE
+
+    #[test]
+    fn package_version() {
+        test("[x]: https://docs.rs/foo/*", "[x]: crate");
+    }
+
+    #[test]
+    fn package_version_fragment() {
+        test("[x]: https://docs.rs/foo/*#fragment", "[x]: crate#fragment");
+    }
+
+    #[test]
+    fn package_version_index() {
+        test("[x]: https://docs.rs/foo/*/index.html", "[x]: crate");
+    }
+
+    #[test]
+    fn package_version_index_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/index.html#fragment",
+            "[x]: crate#fragment",
+        );
+    }
+
+    #[test]
+    fn package_version_slash() {
+        test("[x]: https://docs.rs/foo/*/", "[x]: crate");
+    }
+
+    #[test]
+    fn package_version_slash_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/#fragment",
+            "[x]: crate#fragment",
+        );
+    }
+
+    // ============================================================
+    // LINKS - HAS CRATE, BUT NO MORE SEGMENTS
+
+    #[test]
+    fn package_version_crate() {
+        test("[x]: https://docs.rs/foo/*/foo", "[x]: crate");
+    }
+
+    #[test]
+    fn package_version_crate_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo#fragment",
+            "[x]: crate#fragment",
+        );
+    }
+
+    #[test]
+    fn package_version_crate_index() {
+        test("[x]: https://docs.rs/foo/*/foo/index.html", "[x]: crate");
+    }
+
+    #[test]
+    fn package_version_crate_index_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/index.html#fragment",
+            "[x]: crate#fragment",
+        );
+    }
+
+    #[test]
+    fn package_version_crate_slash() {
+        test("[x]: https://docs.rs/foo/*/foo/", "[x]: crate");
+    }
+
+    #[test]
+    fn package_version_crate_slash_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/#fragment",
+            "[x]: crate#fragment",
+        );
+    }
+
+    // ============================================================
+    // LINKS - MODULE
+
+    #[test]
+    fn module() {
+        test("[x]: https://docs.rs/foo/*/foo/a/b", "[x]: crate::a::b");
+    }
+
+    #[test]
+    fn module_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b#fragment",
+            "[x]: crate::a::b#fragment",
+        );
+    }
+
+    #[test]
+    fn module_index() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/index.html",
+            "[x]: crate::a::b",
+        );
+    }
+
+    #[test]
+    fn module_index_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/index.html#fragment",
+            "[x]: crate::a::b#fragment",
+        );
+    }
+
+    #[test]
+    fn module_slash() {
+        test("[x]: https://docs.rs/foo/*/foo/a/b/", "[x]: crate::a::b");
+    }
+
+    #[test]
+    fn module_slash_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/#fragment",
+            "[x]: crate::a::b#fragment",
+        );
+    }
+
+    // ============================================================
+    // LINKS - ENUM
+
+    #[test]
+    fn root_enum() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/enum.Foo.html",
+            "[x]: crate::Foo",
+        );
+    }
+
+    #[test]
+    fn root_enum_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/enum.Foo.html#fragment",
+            "[x]: crate::Foo#fragment",
+        );
+    }
+
+    #[test]
+    fn root_enum_method() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/enum.Foo.html#method.bar",
+            "[x]: crate::Foo::bar",
+        );
+    }
+
+    #[test]
+    fn root_enum_variant() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/enum.Foo.html#variant.Bar",
+            "[x]: crate::Foo::Bar",
+        );
+    }
+
+    #[test]
+    fn module_enum() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/enum.Foo.html",
+            "[x]: crate::a::b::Foo",
+        );
+    }
+
+    #[test]
+    fn module_enum_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/enum.Foo.html#fragment",
+            "[x]: crate::a::b::Foo#fragment",
+        );
+    }
+
+    #[test]
+    fn module_enum_method() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/enum.Foo.html#method.bar",
+            "[x]: crate::a::b::Foo::bar",
+        );
+    }
+
+    #[test]
+    fn module_enum_variant() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/enum.Foo.html#variant.Bar",
+            "[x]: crate::a::b::Foo::Bar",
+        );
+    }
+
+    // ============================================================
+    // LINKS - FUNCTION
+
+    #[test]
+    fn root_function() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/fn.bar.html",
+            "[x]: crate::bar",
+        );
+    }
+
+    #[test]
+    fn root_function_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/fn.bar.html#fragment",
+            "[x]: crate::bar#fragment",
+        );
+    }
+
+    #[test]
+    fn module_function() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/fn.bar.html",
+            "[x]: crate::a::b::bar",
+        );
+    }
+
+    #[test]
+    fn module_function_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/fn.bar.html#fragment",
+            "[x]: crate::a::b::bar#fragment",
+        );
+    }
+
+    // ============================================================
+    // LINKS - MACRO
+
+    #[test]
+    fn root_macro() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/macro.bar.html",
+            "[x]: crate::bar",
+        );
+    }
+
+    #[test]
+    fn root_macro_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/macro.bar.html#fragment",
+            "[x]: crate::bar#fragment",
+        );
+    }
+
+    #[test]
+    fn module_macro() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/macro.bar.html",
+            "[x]: crate::a::b::bar",
+        );
+    }
+
+    #[test]
+    fn module_macro_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/macro.bar.html#fragment",
+            "[x]: crate::a::b::bar#fragment",
+        );
+    }
+
+    // ============================================================
+    // LINKS - STRUCT
+
+    #[test]
+    fn root_struct() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/struct.Foo.html",
+            "[x]: crate::Foo",
+        );
+    }
+
+    #[test]
+    fn root_struct_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/struct.Foo.html#fragment",
+            "[x]: crate::Foo#fragment",
+        );
+    }
+
+    #[test]
+    fn root_struct_method() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/struct.Foo.html#method.bar",
+            "[x]: crate::Foo::bar",
+        );
+    }
+
+    #[test]
+    fn module_struct() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/struct.Foo.html",
+            "[x]: crate::a::b::Foo",
+        );
+    }
+
+    #[test]
+    fn module_struct_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/struct.Foo.html#fragment",
+            "[x]: crate::a::b::Foo#fragment",
+        );
+    }
+
+    #[test]
+    fn module_struct_method() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/struct.Foo.html#method.bar",
+            "[x]: crate::a::b::Foo::bar",
+        );
+    }
+
+    // ============================================================
+    // LINKS - TRAIT
+
+    #[test]
+    fn root_trait() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/trait.Foo.html",
+            "[x]: crate::Foo",
+        );
+    }
+
+    #[test]
+    fn root_trait_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/trait.Foo.html#fragment",
+            "[x]: crate::Foo#fragment",
+        );
+    }
+
+    #[test]
+    fn root_trait_method() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/trait.Foo.html#tymethod.bar",
+            "[x]: crate::Foo::bar",
+        );
+    }
+
+    #[test]
+    fn module_trait() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/trait.Foo.html",
+            "[x]: crate::a::b::Foo",
+        );
+    }
+
+    #[test]
+    fn module_trait_fragment() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/trait.Foo.html#fragment",
+            "[x]: crate::a::b::Foo#fragment",
+        );
+    }
+
+    #[test]
+    fn module_trait_method() {
+        test(
+            "[x]: https://docs.rs/foo/*/foo/a/b/trait.Foo.html#tymethod.bar",
+            "[x]: crate::a::b::Foo::bar",
+        );
+    }
+
+    // ============================================================
+    // CODE BLOCKS
+
+    #[test]
+    fn two_backticks_doesnt_begin_code_block() {
+        test("``\n## a", "``\n# a");
+    }
+
+    #[test]
+    fn three_backtick_code_block_with_header() {
+        test("```\n## a\n```\n## b", "```\n## a\n```\n# b");
+    }
+
+    #[test]
+    fn four_backtick_code_block_with_header() {
+        test("````\n```\n## a\n````\n## b", "````\n```\n## a\n````\n# b");
+    }
+
+    #[test]
+    fn code_block_with_link() {
+        test(
+            "```\n[a]: https://docs.rs/foo\n```\n[b]: https://docs.rs/foo",
+            "```\n[a]: https://docs.rs/foo\n```\n[b]: crate",
+        );
+    }
+
+    #[test]
+    fn code_block_with_short_line() {
+        test("```\n\n```", "```\n\n```");
+    }
+
+    #[test]
+    fn code_block_with_type() {
+        test("```foo\n## a\n```\n## b", "```foo\n## a\n```\n# b");
+    }
+}
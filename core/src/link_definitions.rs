@@ -0,0 +1,508 @@
+// CommonMark link reference definitions like `[label]: URL "title"`,
+// references to them like `[text][label]` and inline links like `[text](URL)`.
+//
+// Definitions can be within blockquotes and list items,
+// and URL and title can be on the line after label and URL respectively.
+// Multi-line labels and titles are not supported.
+
+use std::ops::Range;
+
+use super::code_span_len;
+
+// ======================================================================
+// LINKDEFINITION - PUBLIC
+
+#[derive(Debug, PartialEq)]
+pub struct LinkDefinition<'a> {
+    // label without brackets, e.g. `foo` of `[foo]: URL`
+    pub label: &'a str,
+    // index of line with URL, relative to first line of definition
+    pub url_line: usize,
+    // position of URL within its line, excluding angle brackets
+    pub url_range: Range<usize>,
+    pub has_angle_brackets: bool,
+    pub line_count: usize,
+}
+
+impl<'a> LinkDefinition<'a> {
+    // Parses link reference definition starting at first of given lines.
+    //
+    // `max_indent` is maximum indentation of the definition,
+    // which is 3 or more within list items.
+    pub fn parse(lines: &[&'a str], max_indent: usize) -> Option<Self> {
+        let first = *lines.first()?;
+        let mut pos = container_prefix_len(first);
+        let indent = first[pos..].len() - first[pos..].trim_start_matches(' ').len();
+        if pos + indent > max_indent.max(pos + 3) {
+            return None;
+        }
+        pos += indent;
+
+        // LABEL
+
+        if !first[pos..].starts_with('[') {
+            return None;
+        }
+        let label_start = pos + 1;
+        let label_end = label_start + label_len(&first[label_start..])?;
+        let label = &first[label_start..label_end];
+        if !first[label_end + 1..].starts_with(':') {
+            return None;
+        }
+        pos = label_end + 2;
+
+        // URL
+
+        let mut url_line = 0;
+        pos = skip_whitespace(first, pos);
+        if is_line_end(&first[pos..]) {
+            url_line = 1;
+            let line = lines.get(1)?;
+            pos = skip_whitespace(line, quote_prefix_len(line));
+            if is_line_end(&line[pos..]) {
+                return None;
+            }
+        }
+        let line = lines[url_line];
+        let (url_range, has_angle_brackets) = destination(&line[pos..])?;
+        let url_range = pos + url_range.start..pos + url_range.end;
+        pos = url_range.end + has_angle_brackets as usize;
+
+        // TITLE
+
+        let mut line_count = url_line + 1;
+        let title_start = skip_whitespace(line, pos);
+        if is_line_end(&line[title_start..]) {
+            // title on next line, if any
+            if let Some(next) = lines.get(line_count) {
+                let title_start = skip_whitespace(next, quote_prefix_len(next));
+                if title_len(&next[title_start..])
+                    .is_some_and(|len| is_line_end(skip(&next[title_start + len..])))
+                {
+                    line_count += 1;
+                }
+            }
+        } else {
+            let len = title_len(&line[title_start..]).filter(|_| title_start > pos)?;
+            if !is_line_end(skip(&line[title_start + len..])) {
+                return None;
+            }
+        }
+
+        Some(Self {
+            label,
+            url_line,
+            url_range,
+            has_angle_brackets,
+            line_count,
+        })
+    }
+}
+
+// ======================================================================
+// LINK - PUBLIC
+
+// Link in a line of text.
+#[derive(Debug, PartialEq)]
+pub enum Link<'a> {
+    // label of `[text][label]` or `[label][]`
+    Reference(&'a str),
+    // label of `[label]`, which may also be just text in brackets like `[WIP]`
+    Shortcut(&'a str),
+    // URL of `[text](URL)`, without angle brackets
+    Inline(&'a str),
+}
+
+// ======================================================================
+// FUNCTIONS - PUBLIC
+
+// Returns length of blockquote markers and list item marker at start of line,
+// e.g. 4 for `> - [foo]: URL`.
+pub fn container_prefix_len(line: &str) -> usize {
+    let pos = quote_prefix_len(line);
+    pos + list_marker_len(&line[pos..]).unwrap_or(0)
+}
+
+// Returns number of blockquote markers `>` at start of line.
+pub fn quote_depth(line: &str) -> usize {
+    line[..quote_prefix_len(line)].matches('>').count()
+}
+
+// Returns column of list item content if line starts a list item, e.g. 2 for `- foo`.
+pub fn list_item_content_column(line: &str) -> Option<usize> {
+    let pos = quote_prefix_len(line);
+    list_marker_len(&line[pos..]).map(|len| pos + len)
+}
+
+// Returns link references and inline links in given line of text.
+//
+// Code spans, footnotes like `[^1]`, alerts like `[!NOTE]`
+// and task list markers like `[x]` are skipped.
+pub fn links(line: &str) -> Vec<Link<'_>> {
+    let mut links = Vec::new();
+    let task_marker_pos = list_item_content_column(line);
+    let mut pos = 0;
+
+    while let Some(offset) = line[pos..].find(['`', '\\', '[']) {
+        let start = pos + offset;
+        match line.as_bytes()[start] {
+            b'`' => {
+                pos = start + code_span_len(&line[start..]);
+                continue;
+            }
+            b'\\' => {
+                pos = start + 1 + line[start + 1..].chars().next().map_or(0, char::len_utf8);
+                continue;
+            }
+            _ => {}
+        }
+
+        let text_start = start + 1;
+        let text_len = match label_len(&line[text_start..]) {
+            Some(len) => len,
+            None => {
+                pos = text_start;
+                continue;
+            }
+        };
+        let text = &line[text_start..text_start + text_len];
+        pos = text_start + text_len + 1;
+
+        let rest = &line[pos..];
+        if let Some(url) = rest.strip_prefix('(') {
+            // `[text](` without valid destination isn't a link either
+            let url_start = skip_whitespace(url, 0);
+            if let Some(range) = inline_destination(&url[url_start..]) {
+                links.push(Link::Inline(&url[url_start..][range]));
+            }
+        } else if let Some(label) = rest.strip_prefix('[') {
+            match label_len(label) {
+                Some(len) => {
+                    links.push(Link::Reference(&label[..len]));
+                    pos += len + 2;
+                }
+                None if label.starts_with(']') => {
+                    links.push(Link::Reference(text));
+                    pos += 2;
+                }
+                None => links.push(Link::Shortcut(text)),
+            }
+        } else {
+            // footnotes, alert markers and task list markers aren't links
+            let is_task_marker = task_marker_pos == Some(start) && matches!(text, "x" | "X");
+            if !text.starts_with(['^', '!']) && !is_task_marker {
+                links.push(Link::Shortcut(text));
+            }
+        }
+    }
+
+    links
+}
+
+// Returns length of whitespace and destination of inline link at start of text,
+// which follows `](`, or `None` if there is no valid destination.
+pub fn inline_destination_len(text: &str) -> Option<usize> {
+    let start = skip_whitespace(text, 0);
+    let range = inline_destination(&text[start..])?;
+    // angle brackets are excluded from range
+    Some(start + range.end + usize::from(text[start..].starts_with('<')))
+}
+
+// Normalizes label for matching, like CommonMark does
+// with case-insensitive matching and collapsed whitespace.
+pub fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+// Returns length of blockquote markers like `> > ` at start of line.
+fn quote_prefix_len(line: &str) -> usize {
+    let mut pos = 0;
+    loop {
+        let rest = &line[pos..];
+        let indent = rest.len() - rest.trim_start_matches(' ').len();
+        if indent > 3 || !rest[indent..].starts_with('>') {
+            return pos;
+        }
+        pos += indent + 1;
+        if line[pos..].starts_with(' ') {
+            pos += 1;
+        }
+    }
+}
+
+// Returns length of list item marker like `- `, `* ` or `1. ` with following spaces.
+fn list_marker_len(text: &str) -> Option<usize> {
+    let indent = text.len() - text.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &text[indent..];
+    let marker_len = if rest.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if (1..=9).contains(&digits) && rest[digits..].starts_with(['.', ')']) {
+            digits + 1
+        } else {
+            return None;
+        }
+    };
+    let spaces = rest[marker_len..].len() - rest[marker_len..].trim_start_matches(' ').len();
+    if spaces == 0 {
+        None
+    } else {
+        // With 5 or more spaces, content is an indented code block after 1 space.
+        Some(indent + marker_len + if spaces > 4 { 1 } else { spaces })
+    }
+}
+
+// Returns length of label up to closing `]`, or `None` if label is invalid.
+fn label_len(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (pos, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' | '\n' | '\r' => return None,
+            ']' => {
+                let label = &text[..pos];
+                return Some(pos).filter(|_| !label.trim().is_empty() && label.len() <= 999);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Returns position of link destination at start of text,
+// and whether it is within angle brackets, excluded from position.
+fn destination(text: &str) -> Option<(Range<usize>, bool)> {
+    if let Some(rest) = text.strip_prefix('<') {
+        let mut escaped = false;
+        for (pos, c) in rest.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '<' | '\n' | '\r' => return None,
+                '>' => return Some((1..1 + pos, true)),
+                _ => {}
+            }
+        }
+        None
+    } else {
+        let mut escaped = false;
+        let mut paren_depth = 0;
+        let mut len = text.len();
+        for (pos, c) in text.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '(' => paren_depth += 1,
+                ')' if paren_depth == 0 => return None,
+                ')' => paren_depth -= 1,
+                _ if c.is_ascii_whitespace() || c.is_ascii_control() => {
+                    len = pos;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        Some((0..len, false)).filter(|_| len > 0 && paren_depth == 0)
+    }
+}
+
+// Returns position of destination of inline link at start of text,
+// excluding angle brackets. Destination without angle brackets ends
+// at whitespace or at `)` which closes the link.
+fn inline_destination(text: &str) -> Option<Range<usize>> {
+    if text.starts_with('<') {
+        return destination(text).map(|(range, _)| range);
+    }
+    let mut escaped = false;
+    let mut paren_depth = 0;
+    for (pos, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '(' => paren_depth += 1,
+            ')' if paren_depth == 0 => return Some(0..pos),
+            ')' => paren_depth -= 1,
+            _ if c.is_ascii_whitespace() => return Some(0..pos).filter(|_| paren_depth == 0),
+            _ if c.is_ascii_control() => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+// Returns length of title like `"title"`, `'title'` or `(title)` at start of text.
+fn title_len(text: &str) -> Option<usize> {
+    let close = match text.chars().next()? {
+        '"' => '"',
+        '\'' => '\'',
+        '(' => ')',
+        _ => return None,
+    };
+    let mut escaped = false;
+    for (pos, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\n' | '\r' => return None,
+            '(' if close == ')' => return None,
+            _ if c == close => return Some(pos + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn is_line_end(text: &str) -> bool {
+    text.trim_end_matches(['\r', '\n']).is_empty()
+}
+
+fn skip(text: &str) -> &str {
+    text.trim_start_matches([' ', '\t'])
+}
+
+fn skip_whitespace(line: &str, pos: usize) -> usize {
+    line.len() - skip(&line[pos..]).len()
+}
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Returns label, URL and line count of definition.
+    fn parse(text: &str) -> Option<(&str, &str, usize)> {
+        let lines: Vec<&str> = text.split_inclusive('\n').collect();
+        LinkDefinition::parse(&lines, 3).map(|definition| {
+            (
+                definition.label,
+                &lines[definition.url_line][definition.url_range],
+                definition.line_count,
+            )
+        })
+    }
+
+    #[test]
+    fn simple() {
+        assert_eq!(parse("[foo]: /url\n"), Some(("foo", "/url", 1)));
+        assert_eq!(parse("[foo]:/url"), Some(("foo", "/url", 1)));
+        assert_eq!(parse("   [foo]: /url"), Some(("foo", "/url", 1)));
+        assert_eq!(parse("    [foo]: /url"), None);
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(parse("[a\\]b]: /url"), Some(("a\\]b", "/url", 1)));
+        assert_eq!(parse("[a[b]: /url"), None);
+        assert_eq!(parse("[ ]: /url"), None);
+        assert_eq!(parse("[foo] : /url"), None);
+        assert_eq!(parse("[foo]"), None);
+    }
+
+    #[test]
+    fn angle_brackets() {
+        assert_eq!(parse("[foo]: <a b> 'c'"), Some(("foo", "a b", 1)));
+        assert_eq!(parse("[foo]: <a"), None);
+    }
+
+    #[test]
+    fn titles() {
+        assert_eq!(parse("[foo]: /url \"title\"  \n"), Some(("foo", "/url", 1)));
+        assert_eq!(parse("[foo]: /url (title)"), Some(("foo", "/url", 1)));
+        assert_eq!(parse("[foo]: /url 'a \\' b'"), Some(("foo", "/url", 1)));
+        assert_eq!(
+            parse("[foo]: /url\"title\""),
+            Some(("foo", "/url\"title\"", 1))
+        );
+        assert_eq!(parse("[foo]: /url \"title\" ok"), None);
+    }
+
+    #[test]
+    fn ordinary_text() {
+        assert_eq!(parse("[note]: this is text"), None);
+        assert_eq!(parse("[foo]: /url(a"), None);
+    }
+
+    #[test]
+    fn next_lines() {
+        assert_eq!(parse("[foo]:\n   /url\n"), Some(("foo", "/url", 2)));
+        assert_eq!(
+            parse("[foo]: /url\n  \"title\"\n"),
+            Some(("foo", "/url", 2))
+        );
+        assert_eq!(
+            parse("[foo]: /url\n\"title\" ok\n"),
+            Some(("foo", "/url", 1))
+        );
+        assert_eq!(parse("[foo]:\n\n/url"), None);
+        assert_eq!(parse("[foo]:"), None);
+    }
+
+    #[test]
+    fn containers() {
+        assert_eq!(parse("> [foo]: /url"), Some(("foo", "/url", 1)));
+        assert_eq!(parse("> > [foo]:\n> > /url"), Some(("foo", "/url", 2)));
+        assert_eq!(parse("- [foo]: /url"), Some(("foo", "/url", 1)));
+        assert_eq!(parse("> 1. [foo]: /url"), Some(("foo", "/url", 1)));
+        assert_eq!(parse("-[foo]: /url"), None);
+    }
+
+    #[test]
+    fn reference_labels() {
+        assert_eq!(
+            links("[a][B c] [d][] [e] ![g][h] `[i]` \\[j]"),
+            [
+                Link::Reference("B c"),
+                Link::Reference("d"),
+                Link::Shortcut("e"),
+                Link::Reference("h")
+            ]
+        );
+        assert_eq!(links("- [x] [^1] [!NOTE] [ ] x[k]"), [Link::Shortcut("k")]);
+        assert_eq!(links("[a [b] c]"), [Link::Shortcut("b")]);
+        assert_eq!(
+            links("[a][ b [c]"),
+            [Link::Shortcut("a"), Link::Shortcut("c")]
+        );
+    }
+
+    #[test]
+    fn inline_links() {
+        assert_eq!(
+            links("[a](url) [b]( <u v> \"t\") [c](f(x)) [d](e"),
+            [
+                Link::Inline("url"),
+                Link::Inline("u v"),
+                Link::Inline("f(x)")
+            ]
+        );
+    }
+
+    #[test]
+    fn normalized_labels() {
+        assert_eq!(normalize_label(" Foo \t Bar "), "foo bar");
+        assert_eq!(normalize_label("`ÄB`"), "`äb`");
+    }
+
+    #[test]
+    fn list_items() {
+        assert_eq!(list_item_content_column("- foo"), Some(2));
+        assert_eq!(list_item_content_column("> 10.  foo"), Some(7));
+        assert_eq!(list_item_content_column("-foo"), None);
+        assert_eq!(quote_depth("> > a"), 2);
+        assert_eq!(quote_depth("a > b"), 0);
+    }
+}
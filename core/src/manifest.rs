@@ -0,0 +1,273 @@
+use std::{
+    fs,
+    path::{self, Path, PathBuf},
+};
+
+use super::{
+    toml::{Toml, Value},
+    Error,
+};
+
+// ======================================================================
+// PACKAGE - PUBLIC
+
+// Information about a package read from its `Cargo.toml`,
+// with fields inherited from workspace resolved.
+#[derive(Debug, PartialEq)]
+pub struct Package {
+    pub name: String,
+    pub version: Option<String>,
+    // Name of library target, i.e. `[lib] name` or package name with `-` replaced by `_`.
+    pub crate_name: String,
+    // Absolute path of readme, or `None` if `readme = false`.
+    pub readme: Option<PathBuf>,
+    // Contents of `Cargo.toml`.
+    pub manifest: String,
+}
+
+impl Package {
+    // Reads package from given `Cargo.toml`.
+    //
+    // Returned paths are absolute.
+    pub fn read(manifest_path: &Path) -> Result<Self, Error> {
+        let manifest_path = &path::absolute(manifest_path)
+            .map_err(|error| Error::IoFailed(format!("{}: {}", manifest_path.display(), error)))?;
+        let manifest = read_file(manifest_path)?;
+        let toml = parse(&manifest, manifest_path)?;
+        let package_dir = manifest_path.parent().unwrap_or(Path::new(""));
+
+        let name = toml
+            .get(&["package"], &["name"])
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid(manifest_path, "missing `package.name`"))?
+            .to_owned();
+
+        let mut workspace = None;
+        let version = match inherited_field(&toml, manifest_path, "version", &mut workspace)? {
+            Some((Value::String(version), _)) => Some(version),
+            Some(_) => return Err(invalid(manifest_path, "`package.version` is not a string")),
+            None => None,
+        };
+
+        let readme = match inherited_field(&toml, manifest_path, "readme", &mut workspace)? {
+            Some((Value::String(readme), dir)) => Some(dir.join(readme)),
+            Some((Value::Other(value), _)) if value == "false" => None,
+            Some((Value::Other(value), _)) if value == "true" => {
+                Some(package_dir.join("README.md"))
+            }
+            Some(_) => return Err(invalid(manifest_path, "`package.readme` is not a string")),
+            None => Some(default_readme(package_dir)),
+        };
+
+        let crate_name = match toml.get(&["lib"], &["name"]) {
+            Some(Value::String(crate_name)) => crate_name.clone(),
+            Some(_) => return Err(invalid(manifest_path, "`lib.name` is not a string")),
+            None => name.replace('-', "_"),
+        };
+
+        Ok(Self {
+            name,
+            version,
+            crate_name,
+            readme,
+            manifest,
+        })
+    }
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+// Returns readme which Cargo uses when `package.readme` isn't given,
+// or `README.md` if there is none.
+fn default_readme(package_dir: &Path) -> PathBuf {
+    ["README.md", "README.txt", "README"]
+        .iter()
+        .map(|name| package_dir.join(name))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| package_dir.join("README.md"))
+}
+
+// Returns value of `package.FIELD` and directory which path values are relative to.
+//
+// If field is `FIELD.workspace = true`, value is read from `workspace.package.FIELD`
+// of workspace root, which is read to `workspace` on first use.
+fn inherited_field(
+    toml: &Toml,
+    manifest_path: &Path,
+    field: &str,
+    workspace: &mut Option<(Toml, PathBuf)>,
+) -> Result<Option<(Value, PathBuf)>, Error> {
+    let package_dir = manifest_path.parent().unwrap_or(Path::new(""));
+
+    let is_inherited = match toml.get(&["package"], &[field]) {
+        Some(Value::Table(table)) => table
+            .iter()
+            .any(|(key, value)| key == "workspace" && *value == Value::Other("true".to_owned())),
+        Some(value) => return Ok(Some((value.clone(), package_dir.to_owned()))),
+        None => {
+            toml.get(&["package"], &[field, "workspace"]) == Some(&Value::Other("true".to_owned()))
+        }
+    };
+    if !is_inherited {
+        return Ok(None);
+    }
+
+    if workspace.is_none() {
+        *workspace = Some(workspace_root(toml, manifest_path)?);
+    }
+    let (workspace_toml, workspace_dir) = workspace.as_ref().unwrap();
+
+    match workspace_toml.get(&["workspace", "package"], &[field]) {
+        Some(value) => Ok(Some((value.clone(), workspace_dir.clone()))),
+        None => Err(Error::InvalidManifest(format!(
+            "{}: `package.{}` is inherited, but workspace has no `workspace.package.{}`",
+            manifest_path.display(),
+            field,
+            field
+        ))),
+    }
+}
+
+// Finds and reads workspace root of package with given manifest,
+// using `package.workspace` if given or else searching parent directories.
+fn workspace_root(toml: &Toml, manifest_path: &Path) -> Result<(Toml, PathBuf), Error> {
+    let package_dir = manifest_path.parent().unwrap_or(Path::new(""));
+
+    if let Some(workspace) = toml
+        .get(&["package"], &["workspace"])
+        .and_then(Value::as_str)
+    {
+        let workspace_dir = package_dir.join(workspace);
+        let workspace_path = workspace_dir.join("Cargo.toml");
+        let workspace_toml = parse(&read_file(&workspace_path)?, &workspace_path)?;
+        return Ok((workspace_toml, workspace_dir));
+    }
+
+    for dir in package_dir.ancestors().skip(1) {
+        let workspace_path = dir.join("Cargo.toml");
+        if workspace_path.is_file() {
+            let workspace_toml = parse(&read_file(&workspace_path)?, &workspace_path)?;
+            if workspace_toml
+                .tables
+                .iter()
+                .any(|table| table == &["workspace"])
+            {
+                return Ok((workspace_toml, dir.to_owned()));
+            }
+        }
+    }
+
+    Err(invalid(manifest_path, "workspace root not found"))
+}
+
+fn invalid(manifest_path: &Path, message: &str) -> Error {
+    Error::InvalidManifest(format!("{}: {}", manifest_path.display(), message))
+}
+
+fn parse(manifest: &str, manifest_path: &Path) -> Result<Toml, Error> {
+    Toml::parse(manifest).map_err(|message| invalid(manifest_path, &message))
+}
+
+fn read_file(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path)
+        .map_err(|error| Error::IoFailed(format!("{}: {}", path.display(), error)))
+}
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Writes given files to a new temporary directory, returning its path.
+    fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join("readme-rustdocifier-test-manifest")
+            .join(name);
+        for (path, content) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn package() {
+        let dir = write_files(
+            "package",
+            &[(
+                "Cargo.toml",
+                "[package]\nname = \"foo-bar\"\nversion = \"0.1.0\"\nreadme = \"docs/README.md\"\n",
+            )],
+        );
+        let package = Package::read(&dir.join("Cargo.toml")).unwrap();
+        assert_eq!(package.name, "foo-bar");
+        assert_eq!(package.version.as_deref(), Some("0.1.0"));
+        assert_eq!(package.crate_name, "foo_bar");
+        assert_eq!(package.readme, Some(dir.join("docs/README.md")));
+    }
+
+    #[test]
+    fn lib_name_and_no_readme() {
+        let dir = write_files(
+            "lib-name",
+            &[(
+                "Cargo.toml",
+                "[package]\nname = \"foo\"\nreadme = false\n[lib]\nname = \"bar\"\n",
+            )],
+        );
+        let package = Package::read(&dir.join("Cargo.toml")).unwrap();
+        assert_eq!(package.version, None);
+        assert_eq!(package.crate_name, "bar");
+        assert_eq!(package.readme, None);
+    }
+
+    #[test]
+    fn inherited_from_workspace() {
+        let dir = write_files(
+            "workspace",
+            &[
+                (
+                    "Cargo.toml",
+                    "[workspace]\n[workspace.package]\nversion = \"1.2.3\"\nreadme = \"README.md\"\n",
+                ),
+                (
+                    "crates/foo/Cargo.toml",
+                    "[package]\nname = \"foo\"\nversion.workspace = true\nreadme = { workspace = true }\n",
+                ),
+            ],
+        );
+        let package = Package::read(&dir.join("crates/foo/Cargo.toml")).unwrap();
+        assert_eq!(package.version.as_deref(), Some("1.2.3"));
+        assert_eq!(package.readme, Some(dir.join("README.md")));
+    }
+
+    #[test]
+    fn inherited_field_missing_from_workspace() {
+        let dir = write_files(
+            "missing-field",
+            &[
+                ("Cargo.toml", "[workspace]\nmembers = [\"foo\"]\n"),
+                (
+                    "foo/Cargo.toml",
+                    "[package]\nname = \"foo\"\nversion.workspace = true\n",
+                ),
+            ],
+        );
+        assert!(matches!(
+            Package::read(&dir.join("foo/Cargo.toml")),
+            Err(Error::InvalidManifest(_))
+        ));
+    }
+
+    #[test]
+    fn missing_manifest() {
+        assert!(matches!(
+            Package::read(Path::new("no/such/Cargo.toml")),
+            Err(Error::IoFailed(_))
+        ));
+    }
+}
@@ -0,0 +1,306 @@
+// Minimal semantic versioning, just enough for checking versions in `docs.rs` URLs.
+//
+// - Versions are like `1.2.3`, `1.2.3-alpha.1` and `1.2.3+build.5`.
+// - Requirements are like `1.2`, `^1.2`, `~1.2.3`, `=1.2.3` and `*`,
+//   where bare versions mean same as `^`, like in `Cargo.toml`.
+
+use std::cmp::Ordering;
+
+// ======================================================================
+// VERSION - PUBLIC
+
+#[derive(Debug, PartialEq)]
+pub struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    // Pre-release identifiers, e.g. `["alpha", "1"]`. Build metadata is ignored.
+    pre: Vec<String>,
+}
+
+impl Version {
+    // Parses version like `1.2.3-alpha.1+build.5`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.split('+').next()?;
+        let (numbers, pre) = match text.split_once('-') {
+            Some((numbers, pre)) => (numbers, parse_pre(pre)?),
+            None => (text, Vec::new()),
+        };
+        let mut numbers = numbers.split('.').map(parse_number);
+        let version = Self {
+            major: numbers.next()??,
+            minor: numbers.next()??,
+            patch: numbers.next()??,
+            pre,
+        };
+        if numbers.next().is_some() {
+            None
+        } else {
+            Some(version)
+        }
+    }
+
+    // Compares precedence of versions, where pre-releases come before releases.
+    fn cmp_precedence(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => cmp_pre(&self.pre, &other.pre),
+            })
+    }
+}
+
+// ======================================================================
+// REQUIREMENT - PUBLIC
+
+#[derive(Debug, PartialEq)]
+pub enum Requirement {
+    // `*`
+    Any,
+    // `^1.2` or `1.2`
+    Caret(Partial),
+    // `~1.2`
+    Tilde(Partial),
+    // `=1.2`
+    Exact(Partial),
+}
+
+// Version with optional minor and patch numbers.
+#[derive(Debug, PartialEq)]
+pub struct Partial {
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Vec<String>,
+}
+
+impl Requirement {
+    pub fn parse(text: &str) -> Option<Self> {
+        if text == "*" {
+            return Some(Requirement::Any);
+        }
+        if let Some(rest) = text.strip_prefix('^') {
+            Some(Requirement::Caret(Partial::parse(rest)?))
+        } else if let Some(rest) = text.strip_prefix('~') {
+            Some(Requirement::Tilde(Partial::parse(rest)?))
+        } else if let Some(rest) = text.strip_prefix('=') {
+            Some(Requirement::Exact(Partial::parse(rest)?))
+        } else {
+            Some(Requirement::Caret(Partial::parse(text)?))
+        }
+    }
+
+    // Returns `true` if given version matches this requirement.
+    //
+    // As with Cargo, pre-release versions match only requirements
+    // with a pre-release of the same `major.minor.patch`.
+    pub fn matches(&self, version: &Version) -> bool {
+        let partial = match self {
+            Requirement::Any => return version.pre.is_empty(),
+            Requirement::Caret(partial)
+            | Requirement::Tilde(partial)
+            | Requirement::Exact(partial) => partial,
+        };
+
+        if !version.pre.is_empty()
+            && (partial.pre.is_empty()
+                || (partial.major, partial.minor, partial.patch)
+                    != (version.major, Some(version.minor), Some(version.patch)))
+        {
+            return false;
+        }
+
+        let lower = Version {
+            major: partial.major,
+            minor: partial.minor.unwrap_or(0),
+            patch: partial.patch.unwrap_or(0),
+            pre: partial.pre.clone(),
+        };
+        if version.cmp_precedence(&lower) == Ordering::Less {
+            return false;
+        }
+
+        // Numbers which must be same as in requirement.
+        let fixed_count = match self {
+            Requirement::Any => 0,
+            Requirement::Exact(_) => 3,
+            Requirement::Tilde(partial) => {
+                if partial.minor.is_some() {
+                    2
+                } else {
+                    1
+                }
+            }
+            Requirement::Caret(partial) => match (partial.major, partial.minor, partial.patch) {
+                (0, Some(0), Some(_)) => 3,
+                (0, Some(_), _) => 2,
+                _ => 1,
+            },
+        };
+        let given = [Some(partial.major), partial.minor, partial.patch];
+        let actual = [version.major, version.minor, version.patch];
+        (0..fixed_count).all(|index| given[index].is_none_or(|n| n == actual[index]))
+    }
+}
+
+impl Partial {
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.split('+').next()?;
+        let (numbers, pre) = match text.split_once('-') {
+            Some((numbers, pre)) => (numbers, parse_pre(pre)?),
+            None => (text, Vec::new()),
+        };
+        let mut numbers = numbers.split('.');
+        let partial = Self {
+            major: parse_number(numbers.next()?)?,
+            minor: match numbers.next() {
+                Some(number) => Some(parse_number(number)?),
+                None => None,
+            },
+            patch: match numbers.next() {
+                Some(number) => Some(parse_number(number)?),
+                None => None,
+            },
+            pre,
+        };
+        if numbers.next().is_some() || (!partial.pre.is_empty() && partial.patch.is_none()) {
+            None
+        } else {
+            Some(partial)
+        }
+    }
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+// Compares pre-release identifiers, where numeric identifiers
+// are compared numerically and come before alphanumeric ones.
+fn cmp_pre(a: &[String], b: &[String]) -> Ordering {
+    for (a, b) in a.iter().zip(b) {
+        let ordering = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+// Parses number without leading zeros.
+fn parse_number(text: &str) -> Option<u64> {
+    if text.is_empty()
+        || !text.bytes().all(|b| b.is_ascii_digit())
+        || (text.len() > 1 && text.starts_with('0'))
+    {
+        None
+    } else {
+        text.parse().ok()
+    }
+}
+
+fn parse_pre(text: &str) -> Option<Vec<String>> {
+    let identifiers: Vec<String> = text.split('.').map(str::to_owned).collect();
+    if identifiers.iter().all(|identifier| {
+        !identifier.is_empty()
+            && identifier
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    }) {
+        Some(identifiers)
+    } else {
+        None
+    }
+}
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(requirement: &str, version: &str) -> bool {
+        Requirement::parse(requirement)
+            .unwrap()
+            .matches(&Version::parse(version).unwrap())
+    }
+
+    #[test]
+    fn versions() {
+        assert_eq!(
+            Version::parse("1.2.3-alpha.1+build.5"),
+            Some(Version {
+                major: 1,
+                minor: 2,
+                patch: 3,
+                pre: vec!["alpha".to_owned(), "1".to_owned()]
+            })
+        );
+        assert_eq!(Version::parse("1.2"), None);
+        assert_eq!(Version::parse("1.2.03"), None);
+        assert_eq!(Version::parse("1.2.3-"), None);
+        assert_eq!(Version::parse("*"), None);
+    }
+
+    #[test]
+    fn precedence() {
+        let versions = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1",
+        ];
+        for pair in versions.windows(2) {
+            let a = Version::parse(pair[0]).unwrap();
+            let b = Version::parse(pair[1]).unwrap();
+            assert_eq!(a.cmp_precedence(&b), Ordering::Less, "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn caret_requirements() {
+        assert!(matches("0.3", "0.3.2"));
+        assert!(matches("^0.3", "0.3.2"));
+        assert!(matches("0.3.1", "0.3.2"));
+        assert!(!matches("0.3.3", "0.3.2"));
+        assert!(!matches("0.2", "0.3.2"));
+        assert!(matches("1", "1.5.0"));
+        assert!(matches("1.2", "1.5.0"));
+        assert!(!matches("1.6", "1.5.0"));
+        assert!(!matches("^0.0.1", "0.0.2"));
+    }
+
+    #[test]
+    fn tilde_and_exact_requirements() {
+        assert!(matches("~1.2", "1.2.9"));
+        assert!(!matches("~1.2", "1.3.0"));
+        assert!(matches("~1", "1.3.0"));
+        assert!(matches("=1.2.3", "1.2.3"));
+        assert!(matches("=1.2.3", "1.2.3+build"));
+        assert!(!matches("=1.2.3", "1.2.4"));
+        assert!(matches("=1.2", "1.2.4"));
+    }
+
+    #[test]
+    fn pre_releases() {
+        assert!(!matches("1.0", "1.0.0-beta.2"));
+        assert!(!matches("*", "1.0.0-beta.2"));
+        assert!(matches("1.0.0-beta", "1.0.0-beta.2"));
+        assert!(!matches("1.0.0-rc", "1.0.0-beta.2"));
+        assert!(matches("=1.0.0-beta.2", "1.0.0-beta.2"));
+        assert!(!matches("0.9.0-beta", "1.0.0-beta.2"));
+    }
+}
@@ -0,0 +1,569 @@
+// Minimal TOML reader, just enough for reading `Cargo.toml`.
+//
+// - Supports tables, array tables, dotted and quoted keys,
+//   strings, arrays and inline tables.
+// - Other values (integers, booleans, dates, ...) are kept as raw text.
+// - Comment lines directly above a key are kept with the key.
+
+// ======================================================================
+// TOML - PUBLIC
+
+pub struct Toml {
+    pub entries: Vec<Entry>,
+    /// Headers of all tables, including empty ones, e.g. `["workspace"]`.
+    pub tables: Vec<Vec<String>>,
+}
+
+pub struct Entry {
+    /// Header of table containing this entry, e.g. `["package"]`.
+    pub table: Vec<String>,
+    /// Possibly dotted key, e.g. `["version", "workspace"]`.
+    pub key: Vec<String>,
+    pub value: Value,
+    /// Comment lines directly above this entry or, if none, comment after it.
+    pub comment: String,
+    /// 1-based line number of the key.
+    pub line: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    String(String),
+    Array(Vec<Value>),
+    Table(Vec<(String, Value)>),
+    Other(String),
+}
+
+impl Toml {
+    /// Parses given TOML, returning error message on failure.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        Parser::new(text).parse()
+    }
+
+    /// Returns value of given key in given table.
+    pub fn get(&self, table: &[&str], key: &[&str]) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|entry| entry.table == table && entry.key == key)
+            .map(|entry| &entry.value)
+    }
+
+    /// Returns entries of given table in order.
+    pub fn table<'a>(&'a self, table: &'a [&'a str]) -> impl Iterator<Item = &'a Entry> + 'a {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.table == table)
+    }
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+}
+
+// ======================================================================
+// PARSER - PRIVATE
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            pos: 0,
+            line: 1,
+        }
+    }
+
+    fn parse(mut self) -> Result<Toml, String> {
+        let mut entries = Vec::new();
+        let mut tables = Vec::new();
+        let mut table = Vec::new();
+        let mut comment_lines: Vec<String> = Vec::new();
+
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                None => break,
+                Some('#') => {
+                    comment_lines.push(self.comment());
+                    self.end_of_line()?;
+                }
+                Some('\r') | Some('\n') => {
+                    comment_lines.clear();
+                    self.end_of_line()?;
+                }
+                Some('[') => {
+                    comment_lines.clear();
+                    table = self.table_header()?;
+                    tables.push(table.clone());
+                    self.end_of_line()?;
+                }
+                Some(_) => {
+                    let line = self.line;
+                    let key = self.key()?;
+                    self.skip_spaces();
+                    self.expect('=')?;
+                    self.skip_spaces();
+                    let value = self.value()?;
+                    self.skip_spaces();
+                    // comment lines above entry take precedence over trailing comment
+                    let trailing_comment = match self.peek() {
+                        Some('#') => self.comment(),
+                        _ => String::new(),
+                    };
+                    let comment = if comment_lines.is_empty() {
+                        trailing_comment
+                    } else {
+                        comment_lines.join(" ")
+                    };
+                    comment_lines.clear();
+                    self.end_of_line()?;
+                    entries.push(Entry {
+                        table: table.clone(),
+                        key,
+                        value,
+                        comment,
+                        line,
+                    });
+                }
+            }
+        }
+
+        Ok(Toml { entries, tables })
+    }
+
+    // ============================================================
+    // HELPERS
+
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line, message)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        self.text[self.pos..].starts_with(s)
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.peek() == Some(c) {
+            self.next();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", c)))
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while let Some(' ' | '\t') = self.peek() {
+            self.next();
+        }
+    }
+
+    // Skips whitespace, newlines and comments.
+    fn skip_whitespace_and_comments(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ' | '\t' | '\r' | '\n') => {
+                    self.next();
+                }
+                Some('#') => {
+                    self.comment();
+                }
+                _ => break,
+            }
+        }
+    }
+
+    // Expects end of line or end of input.
+    fn end_of_line(&mut self) -> Result<(), String> {
+        if self.starts_with("\r\n") {
+            self.next();
+        }
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.next();
+                Ok(())
+            }
+            Some(_) => Err(self.error("expected end of line")),
+        }
+    }
+
+    // Reads comment starting at `#`, returning it without `#`:s.
+    fn comment(&mut self) -> String {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c == '\r' || c == '\n' {
+                break;
+            }
+            self.next();
+        }
+        self.text[start..self.pos]
+            .trim_start_matches('#')
+            .trim()
+            .to_owned()
+    }
+
+    // ============================================================
+    // KEYS
+
+    fn table_header(&mut self) -> Result<Vec<String>, String> {
+        self.expect('[')?;
+        let is_array = self.peek() == Some('[');
+        if is_array {
+            self.next();
+        }
+        self.skip_spaces();
+        let key = self.key()?;
+        self.skip_spaces();
+        self.expect(']')?;
+        if is_array {
+            self.expect(']')?;
+        }
+        self.skip_spaces();
+        if self.peek() == Some('#') {
+            self.comment();
+        }
+        Ok(key)
+    }
+
+    fn key(&mut self) -> Result<Vec<String>, String> {
+        let mut key = vec![self.simple_key()?];
+        loop {
+            self.skip_spaces();
+            if self.peek() != Some('.') {
+                return Ok(key);
+            }
+            self.next();
+            self.skip_spaces();
+            key.push(self.simple_key()?);
+        }
+    }
+
+    fn simple_key(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some('"') => self.basic_string(),
+            Some('\'') => self.literal_string(),
+            _ => {
+                let start = self.pos;
+                while let Some(c) = self.peek() {
+                    if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                        self.next();
+                    } else {
+                        break;
+                    }
+                }
+                if start == self.pos {
+                    Err(self.error("expected key"))
+                } else {
+                    Ok(self.text[start..self.pos].to_owned())
+                }
+            }
+        }
+    }
+
+    // ============================================================
+    // VALUES
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            _ => {
+                let start = self.pos;
+                while let Some(c) = self.peek() {
+                    if matches!(c, ',' | ']' | '}' | '#' | '\r' | '\n') {
+                        break;
+                    }
+                    self.next();
+                }
+                let raw = self.text[start..self.pos].trim_end();
+                if raw.is_empty() {
+                    Err(self.error("expected value"))
+                } else {
+                    Ok(Value::Other(raw.to_owned()))
+                }
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+        let mut array = Vec::new();
+        loop {
+            self.skip_whitespace_and_comments();
+            if self.peek() == Some(']') {
+                self.next();
+                return Ok(Value::Array(array));
+            }
+            array.push(self.value()?);
+            self.skip_whitespace_and_comments();
+            match self.next() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(array)),
+                _ => return Err(self.error("expected `,` or `]` in array")),
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+        let mut table = Vec::new();
+        loop {
+            self.skip_spaces();
+            if self.peek() == Some('}') {
+                self.next();
+                return Ok(Value::Table(table));
+            }
+            let key = self.key()?.join(".");
+            self.skip_spaces();
+            self.expect('=')?;
+            self.skip_spaces();
+            table.push((key, self.value()?));
+            self.skip_spaces();
+            match self.next() {
+                Some(',') => {}
+                Some('}') => return Ok(Value::Table(table)),
+                _ => return Err(self.error("expected `,` or `}` in inline table")),
+            }
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        let is_multiline = self.starts_with("\"\"\"");
+        if is_multiline {
+            self.pos += 3;
+            self.skip_first_newline();
+        } else {
+            self.expect('"')?;
+        }
+
+        let mut result = String::new();
+        loop {
+            if is_multiline && self.starts_with("\"\"\"") {
+                self.pos += 3;
+                return Ok(result);
+            }
+            if !is_multiline && matches!(self.peek(), Some('\r' | '\n')) {
+                return Err(self.error("unterminated string"));
+            }
+            match self.next() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') if !is_multiline => return Ok(result),
+                Some('\\') => match self.next() {
+                    Some('b') => result.push('\u{8}'),
+                    Some('t') => result.push('\t'),
+                    Some('n') => result.push('\n'),
+                    Some('f') => result.push('\u{c}'),
+                    Some('r') => result.push('\r'),
+                    Some('"') => result.push('"'),
+                    Some('\\') => result.push('\\'),
+                    Some('u') => result.push(self.unicode_escape(4)?),
+                    Some('U') => result.push(self.unicode_escape(8)?),
+                    Some(' ' | '\t' | '\r' | '\n') if is_multiline => {
+                        while let Some(' ' | '\t' | '\r' | '\n') = self.peek() {
+                            self.next();
+                        }
+                    }
+                    _ => return Err(self.error("invalid escape in string")),
+                },
+                Some(c) => result.push(c),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        let is_multiline = self.starts_with("'''");
+        if is_multiline {
+            self.pos += 3;
+            self.skip_first_newline();
+        } else {
+            self.expect('\'')?;
+        }
+
+        let start = self.pos;
+        loop {
+            if is_multiline && self.starts_with("'''") {
+                let result = self.text[start..self.pos].to_owned();
+                self.pos += 3;
+                return Ok(result);
+            }
+            if !is_multiline && matches!(self.peek(), Some('\r' | '\n')) {
+                return Err(self.error("unterminated string"));
+            }
+            match self.next() {
+                None => return Err(self.error("unterminated string")),
+                Some('\'') if !is_multiline => return Ok(self.text[start..self.pos - 1].to_owned()),
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn skip_first_newline(&mut self) {
+        if self.starts_with("\r\n") {
+            self.next();
+        }
+        if self.peek() == Some('\n') {
+            self.next();
+        }
+    }
+
+    fn unicode_escape(&mut self, len: usize) -> Result<char, String> {
+        let hex = self.text.get(self.pos..self.pos + len);
+        match hex
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32)
+        {
+            Some(c) => {
+                self.pos += len;
+                Ok(c)
+            }
+            None => Err(self.error("invalid unicode escape in string")),
+        }
+    }
+}
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ============================================================
+    // HELPERS
+
+    fn string(s: &str) -> Value {
+        Value::String(s.to_owned())
+    }
+
+    // ============================================================
+    // ERRORS
+
+    #[test]
+    fn unterminated_string() {
+        assert_eq!(
+            Toml::parse("[a]\nb = \"c\n").err(),
+            Some("line 2: unterminated string".to_owned())
+        );
+    }
+
+    #[test]
+    fn garbage_after_value() {
+        assert_eq!(
+            Toml::parse("a = \"b\" c").err(),
+            Some("line 1: expected end of line".to_owned())
+        );
+    }
+
+    // ============================================================
+    // KEYS
+
+    #[test]
+    fn dotted_and_quoted_keys() {
+        let toml = Toml::parse("[target.'cfg(unix)'.\"x\"]\na . b = 1\n").unwrap();
+        assert_eq!(
+            toml.get(&["target", "cfg(unix)", "x"], &["a", "b"]),
+            Some(&Value::Other("1".to_owned()))
+        );
+    }
+
+    #[test]
+    fn array_table() {
+        let toml = Toml::parse("[[bin]]\nname = \"a\"\n").unwrap();
+        assert_eq!(toml.get(&["bin"], &["name"]), Some(&string("a")));
+    }
+
+    #[test]
+    fn empty_tables() {
+        let toml = Toml::parse("[workspace]\n\n[a.b]\n").unwrap();
+        assert!(toml.entries.is_empty());
+        assert_eq!(toml.tables, [vec!["workspace"], vec!["a", "b"]]);
+    }
+
+    // ============================================================
+    // VALUES
+
+    #[test]
+    fn strings() {
+        let toml = Toml::parse(concat!(
+            "a = \"x\\ty\\u00e4\"\n",
+            "b = 'x\\ty'\n",
+            "c = \"\"\"\nx\\\n   y\"\"\"\n",
+            "d = '''\nx\ny'''\n",
+        ))
+        .unwrap();
+        assert_eq!(toml.get(&[], &["a"]), Some(&string("x\tyä")));
+        assert_eq!(toml.get(&[], &["b"]), Some(&string("x\\ty")));
+        assert_eq!(toml.get(&[], &["c"]), Some(&string("xy")));
+        assert_eq!(toml.get(&[], &["d"]), Some(&string("x\ny")));
+    }
+
+    #[test]
+    fn multiline_array_with_comments() {
+        let toml = Toml::parse("a = [\n  \"x\", # comment\n  \"y\",\n]\n").unwrap();
+        assert_eq!(
+            toml.get(&[], &["a"]),
+            Some(&Value::Array(vec![string("x"), string("y")]))
+        );
+    }
+
+    #[test]
+    fn inline_table() {
+        let toml = Toml::parse("a = { version = \"1\", optional = true }").unwrap();
+        assert_eq!(
+            toml.get(&[], &["a"]),
+            Some(&Value::Table(vec![
+                ("version".to_owned(), string("1")),
+                ("optional".to_owned(), Value::Other("true".to_owned())),
+            ]))
+        );
+    }
+
+    // ============================================================
+    // COMMENTS
+
+    #[test]
+    fn comments() {
+        let toml = Toml::parse("# x\n## y\na = 1\n\n# z\n\nb = 2 # w\nc = 3\n").unwrap();
+        let comments: Vec<_> = toml.entries.iter().map(|e| e.comment.as_str()).collect();
+        assert_eq!(comments, ["x y", "w", ""]);
+    }
+
+    #[test]
+    fn comment_lines_and_trailing_comment() {
+        let toml = Toml::parse("[features]\n# c\nfoo = [\"a\"] # t\nbar = [] # u\n").unwrap();
+        let comments: Vec<_> = toml.entries.iter().map(|e| e.comment.as_str()).collect();
+        assert_eq!(comments, ["c", "u"]);
+    }
+}
@@ -0,0 +1,86 @@
+// Expansion of `include_readme!`.
+//
+// Stable Rust has no way for procedural macros to tell Cargo which files they read,
+// and `#![doc = ...]` accepts only an expression which expands to a string literal,
+// so an unnamed `include_bytes!` constant can't be used for tracking files.
+// Instead each file is included with `include_str!` as message of `env!`,
+// which is expanded and so tracked by Cargo, but not used since the variable exists.
+//
+// `CARGO_PKG_VERSION_PRE` is empty unless package has a pre-release version,
+// in which case values of `env!` are hidden within an HTML comment.
+//
+// Code is returned as string, so that this can be tested outside of procedural macros.
+
+use std::path::PathBuf;
+
+// ======================================================================
+// FUNCTIONS - PUBLIC
+
+// Returns code of `::core::concat!("RUSTDOCIFIED", ...)` which tracks all files.
+//
+// Files with non-UTF-8 paths are skipped, since those can't be given to `include_str!`.
+pub fn include_readme_code(rustdocified: &str, files: &[PathBuf], version_pre: &str) -> String {
+    let mut code = format!("::core::concat!({:?}", rustdocified);
+    if !version_pre.is_empty() {
+        code.push_str(", \"<!-- \"");
+    }
+    for path in files.iter().filter_map(|path| path.to_str()) {
+        code.push_str(&format!(
+            ", ::core::env!(\"CARGO_PKG_VERSION_PRE\", ::core::include_str!({:?}))",
+            path
+        ));
+    }
+    if !version_pre.is_empty() {
+        code.push_str(", \" -->\"");
+    }
+    code.push(')');
+    code
+}
+
+// ======================================================================
+// TESTS
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_files() {
+        assert_eq!(
+            include_readme_code("a\n", &[], ""),
+            "::core::concat!(\"a\\n\")"
+        );
+    }
+
+    #[test]
+    fn files() {
+        assert_eq!(
+            include_readme_code(
+                "a \"b\"",
+                &[
+                    PathBuf::from("/foo/README.md"),
+                    PathBuf::from("C:\\foo\\a \"b\".rs"),
+                ],
+                ""
+            ),
+            concat!(
+                "::core::concat!(\"a \\\"b\\\"\"",
+                ", ::core::env!(\"CARGO_PKG_VERSION_PRE\", ::core::include_str!(\"/foo/README.md\"))",
+                ", ::core::env!(\"CARGO_PKG_VERSION_PRE\", ::core::include_str!(\"C:\\\\foo\\\\a \\\"b\\\".rs\"))",
+                ")"
+            )
+        );
+    }
+
+    #[test]
+    fn pre_release_version() {
+        assert_eq!(
+            include_readme_code("a", &[PathBuf::from("/foo/README.md")], "beta.1"),
+            concat!(
+                "::core::concat!(\"a\", \"<!-- \"",
+                ", ::core::env!(\"CARGO_PKG_VERSION_PRE\", ::core::include_str!(\"/foo/README.md\"))",
+                ", \" -->\")"
+            )
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Procedural macros of [`readme-rustdocifier`](https://docs.rs/readme-rustdocifier).
+//!
+//! Use these through `readme-rustdocifier` with feature `macros` enabled.
+
+#![forbid(unsafe_code)]
+
+mod expansion;
+
+use std::env;
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+use readme_rustdocifier_core::{Error, ReadmeRustdocifier};
+
+// ======================================================================
+// MACROS - PUBLIC
+
+/// Rustdocifies `README.md` of the package being compiled, without a build script.
+///
+/// ```ignore
+/// #![doc = readme_rustdocifier::include_readme!()]
+/// ```
+///
+/// - Package name, version and crate name are read from Cargo
+///   like with `ReadmeRustdocifier::from_env`.
+/// - Readme is read from `package.readme` of `Cargo.toml`, or `README.md` by default.
+/// - Conversion errors are reported as compile errors.
+///
+/// Readme, `Cargo.toml` and embedded files are included with `include_str!`
+/// within the expansion, so that Cargo rebuilds the crate when those change.
+#[proc_macro]
+pub fn include_readme(input: TokenStream) -> TokenStream {
+    if !input.is_empty() {
+        return compile_error("include_readme! takes no arguments");
+    }
+    match expand() {
+        Ok(code) => code
+            .parse()
+            .unwrap_or_else(|error: proc_macro::LexError| compile_error(&error.to_string())),
+        Err(error) => compile_error(error.to_string().trim_end()),
+    }
+}
+
+// ======================================================================
+// FUNCTIONS - PRIVATE
+
+// Returns tokens of `::core::compile_error!("message")`.
+fn compile_error(message: &str) -> TokenStream {
+    core_macro("compile_error", string(message).into())
+}
+
+// Returns tokens of `::core::NAME!(args)`.
+fn core_macro(name: &str, args: TokenStream) -> TokenStream {
+    let tokens: [TokenTree; 8] = [
+        Punct::new(':', Spacing::Joint).into(),
+        Punct::new(':', Spacing::Alone).into(),
+        Ident::new("core", Span::call_site()).into(),
+        Punct::new(':', Spacing::Joint).into(),
+        Punct::new(':', Spacing::Alone).into(),
+        Ident::new(name, Span::call_site()).into(),
+        Punct::new('!', Spacing::Alone).into(),
+        Group::new(Delimiter::Parenthesis, args).into(),
+    ];
+    tokens.into_iter().collect()
+}
+
+fn expand() -> Result<String, Error> {
+    let (rustdocified, files) = ReadmeRustdocifier::from_env()?.rustdocify_readme_with_files()?;
+    let version_pre = env::var("CARGO_PKG_VERSION_PRE").unwrap_or_default();
+    Ok(expansion::include_readme_code(
+        &rustdocified,
+        &files,
+        &version_pre,
+    ))
+}
+
+fn string(value: &str) -> TokenTree {
+    Literal::string(value).into()
+}
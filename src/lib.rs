@@ -2,6 +2,10 @@
 #![deny(missing_docs)]
 #![forbid(unsafe_code)]
 
-pub use inner::{rustdocify, Error};
+pub use readme_rustdocifier_core::{
+    docs_rs_url, rustdocify, Check, DocsLayout, DocsRsUrl, Error, ItemKind, ReadmeRustdocifier,
+    Severity, VersionPolicy, Warning, WarningKind,
+};
 
-mod inner;
+#[cfg(feature = "macros")]
+pub use readme_rustdocifier_macros::include_readme;